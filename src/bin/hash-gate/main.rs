@@ -0,0 +1,373 @@
+use clap::{Args, Parser, Subcommand};
+use hash_gate::{
+    client::HashGateClient,
+    config::HashGateConfig,
+    error::HashGateError,
+    operations::{delivery::DeliveryMethod, user::User},
+};
+use output::OutputFormat;
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, IsTerminal},
+    process::ExitCode,
+};
+use uuid::Uuid;
+
+mod output;
+
+#[derive(Parser, Debug)]
+#[command(name = "hash-gate", version)]
+/// Administer a HashGate user pool.
+///
+/// Credentials are read from the `HASHGATE_CLIENT_ID`
+/// and `HASHGATE_CLIENT_SECRET` environment variables.
+struct Cli {
+    /// How results are printed.
+    #[arg(long, short, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Look up and manage users.
+    #[command(subcommand)]
+    User(UserCommand),
+
+    /// Walk a user through a password reset.
+    #[command(subcommand)]
+    Reset(ResetCommand),
+
+    /// Inspect the user pool.
+    #[command(subcommand)]
+    Pool(PoolCommand),
+
+    /// Inspect user auth tokens.
+    #[command(subcommand)]
+    Token(TokenCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum UserCommand {
+    /// Get a user by id.
+    Get { user_id: Uuid },
+
+    /// Create a user.
+    Create {
+        username: String,
+
+        /// The email of the user.
+        #[arg(long)]
+        email: Option<String>,
+
+        #[command(flatten)]
+        password: PasswordArgs,
+
+        /// Add the user to a group, can be repeated.
+        #[arg(long = "group")]
+        groups: Vec<i32>,
+
+        /// Add the user to the admin group.
+        #[arg(long)]
+        admin: bool,
+
+        /// Send the user a verification code.
+        #[arg(long)]
+        send_verification: bool,
+    },
+
+    /// Update a user's password.
+    UpdatePassword {
+        user_id: Uuid,
+
+        #[command(flatten)]
+        password: PasswordArgs,
+    },
+
+    /// Start verifying a user, or complete it with `--session` and `--code`.
+    Verify {
+        user_id: Uuid,
+
+        #[command(flatten)]
+        delivery: DeliveryArgs,
+
+        /// The verification session to complete.
+        #[arg(long, requires = "code")]
+        session: Option<Uuid>,
+
+        /// The verification code the user was sent.
+        #[arg(long, requires = "session")]
+        code: Option<String>,
+    },
+
+    /// Set a custom attribute, the value is parsed as JSON falling back to a string.
+    SetAttribute {
+        user_id: Uuid,
+        key: String,
+        value: String,
+    },
+
+    /// Get all custom attributes of a user.
+    GetAttributes { user_id: Uuid },
+}
+
+#[derive(Subcommand, Debug)]
+enum ResetCommand {
+    /// Start a password reset for the user with an email.
+    Init {
+        email: String,
+
+        #[command(flatten)]
+        delivery: DeliveryArgs,
+    },
+
+    /// Verify a password reset with the code the user was sent.
+    Verify {
+        verification_session_id: Uuid,
+        code: String,
+    },
+
+    /// Complete a password reset.
+    Complete {
+        password_reset_session_id: Uuid,
+
+        #[command(flatten)]
+        password: PasswordArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PoolCommand {
+    /// Show the user pool and its password policy.
+    Info,
+}
+
+#[derive(Subcommand, Debug)]
+enum TokenCommand {
+    /// Show the user an auth token belongs to.
+    Inspect { token: String },
+}
+
+#[derive(Args, Debug)]
+struct DeliveryArgs {
+    /// Return the code instead of having HashGate deliver it.
+    #[arg(long)]
+    return_code: bool,
+}
+impl DeliveryArgs {
+    fn method(&self) -> DeliveryMethod {
+        if self.return_code {
+            DeliveryMethod::ReturnCode
+        } else {
+            DeliveryMethod::default()
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+struct PasswordArgs {
+    /// The password, prefer `--password-stdin` as this ends up in shell history.
+    #[arg(long)]
+    password: Option<String>,
+
+    /// Read the password from stdin (ex: `printf %s "$PASSWORD" | hash-gate ...`).
+    #[arg(long)]
+    password_stdin: bool,
+}
+impl PasswordArgs {
+    /// Get the password, reading it from stdin if `--password-stdin` is set.
+    ///
+    /// NOTE: A terminal would echo the password as it's typed, so it has to be piped in.
+    fn read(self) -> Result<String, CliError> {
+        if let Some(password) = self.password {
+            return Ok(password);
+        }
+
+        if io::stdin().is_terminal() {
+            return Err(CliError::Usage(
+                "The Password Has To Be Piped Into --password-stdin, A Terminal Would Echo It",
+            ));
+        }
+
+        let mut password = String::new();
+        io::stdin().lock().read_line(&mut password)?;
+
+        Ok(password.trim_end_matches(['\r', '\n']).to_owned())
+    }
+}
+
+/// The errors a command can fail with.
+enum CliError {
+    HashGate(HashGateError),
+    Io(io::Error),
+    Json(serde_json::Error),
+    Usage(&'static str),
+}
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::HashGate(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "Error: IO: {e}"),
+            Self::Json(e) => write!(f, "Error: JSON: {e}"),
+            Self::Usage(e) => write!(f, "Error: {e}"),
+        }
+    }
+}
+impl From<HashGateError> for CliError {
+    fn from(value: HashGateError) -> Self {
+        Self::HashGate(value)
+    }
+}
+impl From<io::Error> for CliError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+impl From<serde_json::Error> for CliError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), CliError> {
+    let config = HashGateConfig::try_from_env()?;
+    let mut client = HashGateClient::try_new(config).await?;
+
+    let result = match cli.command {
+        Command::User(command) => run_user(command, &mut client).await?,
+        Command::Reset(command) => run_reset(command, &mut client).await?,
+        Command::Pool(PoolCommand::Info) => {
+            let pool = client.get_pool().await?;
+            let password_policy = client.get_password_policy().await?;
+            json!({ "pool": pool, "passwordPolicy": password_policy })
+        }
+        Command::Token(TokenCommand::Inspect { token }) => {
+            let user = User::try_from_token(&token, &mut client).await?;
+            json!(user)
+        }
+    };
+
+    output::print(&result, cli.output)?;
+
+    Ok(())
+}
+
+async fn run_user(command: UserCommand, client: &mut HashGateClient) -> Result<Value, CliError> {
+    match command {
+        UserCommand::Get { user_id } => {
+            let user = User::try_from_id(user_id, client).await?;
+            Ok(json!(user))
+        }
+        UserCommand::Create {
+            username,
+            email,
+            password,
+            groups,
+            admin,
+            send_verification,
+        } => {
+            let password = password.read()?;
+
+            let mut registration = client
+                .register(&username, &password)
+                .send_verification(send_verification);
+            if let Some(email) = &email {
+                registration = registration.email(email);
+            }
+            for group_id in groups {
+                registration = registration.group(group_id);
+            }
+            if admin {
+                registration = registration.admin();
+            }
+
+            let user = registration.send().await?;
+            Ok(json!(user))
+        }
+        UserCommand::UpdatePassword { user_id, password } => {
+            let password = password.read()?;
+
+            let mut user = User::try_from_id(user_id, client).await?;
+            let resp = user.update_password(password, client).await?;
+            Ok(json!(resp))
+        }
+        UserCommand::Verify {
+            user_id,
+            delivery,
+            session,
+            code,
+        } => {
+            if let (Some(session), Some(code)) = (session, code) {
+                let resp = client.verify(session, &code).await?;
+                return Ok(json!(resp));
+            }
+
+            let mut user = User::try_from_id(user_id, client).await?;
+            let resp = user.init_verification(client, &delivery.method()).await?;
+            Ok(json!(resp))
+        }
+        UserCommand::SetAttribute {
+            user_id,
+            key,
+            value,
+        } => {
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+
+            let user = User::try_from_id(user_id, client).await?;
+            user.set_custom_attribute(client, &key, &value).await?;
+            Ok(json!({ key: value }))
+        }
+        UserCommand::GetAttributes { user_id } => {
+            let user = User::try_from_id(user_id, client).await?;
+            let attributes = user.get_custom_attributes(client).await?;
+            Ok(attributes)
+        }
+    }
+}
+
+async fn run_reset(command: ResetCommand, client: &mut HashGateClient) -> Result<Value, CliError> {
+    match command {
+        ResetCommand::Init { email, delivery } => {
+            let resp = client
+                .init_password_reset(&email, &delivery.method())
+                .await?;
+            Ok(json!(resp))
+        }
+        ResetCommand::Verify {
+            verification_session_id,
+            code,
+        } => {
+            let resp = client
+                .verify_password_reset(&verification_session_id, &code)
+                .await?;
+            Ok(json!(resp))
+        }
+        ResetCommand::Complete {
+            password_reset_session_id,
+            password,
+        } => {
+            let password = password.read()?;
+
+            let resp = client
+                .reset_user_password(&password_reset_session_id, &password)
+                .await?;
+            Ok(json!(resp))
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+/// How command results are printed.
+pub enum OutputFormat {
+    /// A human readable table.
+    #[default]
+    Table,
+
+    /// Pretty printed JSON, for piping into other tools.
+    Json,
+}
+
+/// Print a command result in the given format.
+pub fn print<T: Serialize>(value: &T, format: OutputFormat) -> Result<(), serde_json::Error> {
+    let value = serde_json::to_value(value)?;
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&value)?),
+        OutputFormat::Table => print!("{}", render_table(&value)),
+    }
+
+    Ok(())
+}
+
+/// Render a value as a table, objects become key/value rows and
+/// arrays of objects get a column per key.
+fn render_table(value: &Value) -> String {
+    match value {
+        Value::Object(fields) => {
+            let rows = fields
+                .iter()
+                .map(|(key, value)| vec![key.clone(), render_cell(value)])
+                .collect();
+            render_rows(&["FIELD".to_owned(), "VALUE".to_owned()], rows)
+        }
+        Value::Array(items) if items.iter().all(Value::is_object) && !items.is_empty() => {
+            let mut headers: Vec<String> = Vec::new();
+            for item in items.iter().filter_map(Value::as_object) {
+                for key in item.keys() {
+                    if !headers.contains(key) {
+                        headers.push(key.clone());
+                    }
+                }
+            }
+
+            let rows = items
+                .iter()
+                .map(|item| {
+                    headers
+                        .iter()
+                        .map(|key| item.get(key).map(render_cell).unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+            let headers: Vec<String> = headers.iter().map(|key| key.to_uppercase()).collect();
+            render_rows(&headers, rows)
+        }
+        Value::Array(items) => items.iter().map(|item| render_cell(item) + "\n").collect(),
+        value => render_cell(value) + "\n",
+    }
+}
+
+/// Render a single cell, nested values are kept as compact JSON.
+fn render_cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_owned(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Render rows under headers, padding every column to its widest cell.
+fn render_rows(headers: &[String], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(headers.to_vec()).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<String>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+
+    table
+}
@@ -0,0 +1,347 @@
+use crate::error::HashGateError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+#[derive(Deserialize, Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+/// The state of a `CircuitBreaker`, for health checks.
+pub enum CircuitState {
+    /// Requests are sent to HashGate as usual.
+    Closed,
+
+    /// Requests fail fast with `HashGateError::CircuitOpen`.
+    Open,
+
+    /// A limited number of probe requests are let through to check if HashGate recovered.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { outcomes: VecDeque<bool> },
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, successes: u32 },
+}
+
+#[derive(Clone)]
+/// Stops sending requests to HashGate while it's failing, so callers fail
+/// fast instead of waiting on timeouts.
+///
+/// The circuit opens when the failure rate over the last requests reaches the
+/// threshold, stays open for the open duration, then lets probe requests
+/// through (half open), closing again once they all succeed.
+/// Failures are requests that got no response, or a 5xx response.
+///
+/// NOTE: Clones share state, so clones of a client share the same circuit.
+pub struct CircuitBreaker {
+    failure_rate_threshold: f64,
+    open_duration: Duration,
+    half_open_probes: u32,
+    window_size: usize,
+    minimum_requests: usize,
+    clock: Arc<dyn Fn() -> Instant + Send + Sync>,
+    state: Arc<Mutex<BreakerState>>,
+}
+impl std::fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("failure_rate_threshold", &self.failure_rate_threshold)
+            .field("open_duration", &self.open_duration)
+            .field("half_open_probes", &self.half_open_probes)
+            .field("window_size", &self.window_size)
+            .field("minimum_requests", &self.minimum_requests)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker::new(0.5, Duration::from_secs(30), 3)
+    }
+}
+impl CircuitBreaker {
+    /// Create a `CircuitBreaker` opening at a failure rate (`0.0` to `1.0`)
+    /// over the last 20 requests, once at least 10 requests were made.
+    pub fn new(
+        failure_rate_threshold: f64,
+        open_duration: Duration,
+        half_open_probes: u32,
+    ) -> CircuitBreaker {
+        CircuitBreaker {
+            failure_rate_threshold,
+            open_duration,
+            half_open_probes: half_open_probes.max(1),
+            window_size: 20,
+            minimum_requests: 10,
+            clock: Arc::new(Instant::now),
+            state: Arc::new(Mutex::new(BreakerState::Closed {
+                outcomes: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Set how many of the last requests the failure rate is calculated over.
+    pub fn window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size.max(1);
+        self
+    }
+
+    /// Set how many requests have to be made before the circuit can open.
+    pub fn minimum_requests(mut self, minimum_requests: usize) -> Self {
+        self.minimum_requests = minimum_requests;
+        self
+    }
+
+    /// Set the clock the circuit reads the time from (ex: a fake clock in tests).
+    pub fn clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Get the current time from the clock.
+    fn now(&self) -> Instant {
+        (self.clock)()
+    }
+
+    /// Lock the state, recovering it if a thread panicked while holding the lock
+    /// (every update leaves it valid, so it can still be used).
+    fn lock_state(&self) -> MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let state = self.lock_state();
+
+        match *state {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { until } if self.now() < until => CircuitState::Open,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Try to get a permit to send a request, returning
+    /// `HashGateError::CircuitOpen` if the request should fail fast.
+    pub(crate) fn try_acquire(&self) -> Result<CircuitPermit, HashGateError> {
+        let mut state = self.lock_state();
+
+        if let BreakerState::Open { until } = *state {
+            if self.now() < until {
+                return Err(HashGateError::CircuitOpen);
+            }
+            *state = BreakerState::HalfOpen {
+                in_flight: 0,
+                successes: 0,
+            };
+        }
+
+        match &mut *state {
+            BreakerState::HalfOpen { in_flight, .. } => {
+                if *in_flight >= self.half_open_probes {
+                    return Err(HashGateError::CircuitOpen);
+                }
+                *in_flight += 1;
+                Ok(CircuitPermit::new(self, true))
+            }
+            _ => Ok(CircuitPermit::new(self, false)),
+        }
+    }
+
+    /// Record the outcome of a request.
+    fn record(&self, is_probe: bool, is_success: bool) {
+        let mut state = self.lock_state();
+
+        match &mut *state {
+            BreakerState::Closed { outcomes } => {
+                outcomes.push_back(!is_success);
+                while outcomes.len() > self.window_size {
+                    outcomes.pop_front();
+                }
+
+                let failures = outcomes.iter().filter(|is_failure| **is_failure).count();
+                if outcomes.len() >= self.minimum_requests
+                    && failures as f64 / outcomes.len() as f64 >= self.failure_rate_threshold
+                {
+                    *state = BreakerState::Open {
+                        until: self.now() + self.open_duration,
+                    };
+                }
+            }
+            BreakerState::HalfOpen {
+                in_flight,
+                successes,
+            } => {
+                if is_probe {
+                    *in_flight = in_flight.saturating_sub(1);
+                }
+
+                if !is_success {
+                    *state = BreakerState::Open {
+                        until: self.now() + self.open_duration,
+                    };
+                } else if is_probe {
+                    *successes += 1;
+                    if *successes >= self.half_open_probes {
+                        *state = BreakerState::Closed {
+                            outcomes: VecDeque::new(),
+                        };
+                    }
+                }
+            }
+            // Requests let through before the circuit opened don't change anything
+            BreakerState::Open { .. } => {}
+        }
+    }
+
+    /// Give back a probe slot without recording an outcome (ex: the request was dropped).
+    fn release(&self) {
+        if let BreakerState::HalfOpen { in_flight, .. } = &mut *self.lock_state() {
+            *in_flight = in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// Permission to send a request through a `CircuitBreaker`,
+/// a probe slot is given back if it's dropped without recording an outcome.
+pub(crate) struct CircuitPermit {
+    breaker: CircuitBreaker,
+    is_probe: bool,
+    is_recorded: bool,
+}
+impl CircuitPermit {
+    fn new(breaker: &CircuitBreaker, is_probe: bool) -> CircuitPermit {
+        CircuitPermit {
+            breaker: breaker.clone(),
+            is_probe,
+            is_recorded: false,
+        }
+    }
+
+    /// Record the outcome of the request.
+    pub(crate) fn record(mut self, is_success: bool) {
+        self.is_recorded = true;
+        self.breaker.record(self.is_probe, is_success);
+    }
+}
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.is_probe && !self.is_recorded {
+            self.breaker.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A breaker opening at half of the last 4 requests failing, with a fake clock.
+    fn breaker() -> (CircuitBreaker, Arc<Mutex<Instant>>) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let clock = now.clone();
+        let breaker = CircuitBreaker::new(0.5, Duration::from_secs(30), 1)
+            .window_size(4)
+            .minimum_requests(4)
+            .clock(move || *clock.lock().unwrap());
+
+        (breaker, now)
+    }
+
+    fn send(breaker: &CircuitBreaker, is_success: bool) {
+        breaker.try_acquire().unwrap().record(is_success);
+    }
+
+    fn open(breaker: &CircuitBreaker) {
+        for is_success in [true, true, false, false] {
+            send(breaker, is_success);
+        }
+    }
+
+    #[test]
+    fn opens_at_the_threshold() {
+        let (breaker, _) = breaker();
+
+        for is_success in [true, true, false] {
+            send(&breaker, is_success);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        send(&breaker, false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.try_acquire(),
+            Err(HashGateError::CircuitOpen)
+        ));
+    }
+
+    #[test]
+    fn half_opens_after_the_open_duration() {
+        let (breaker, now) = breaker();
+        open(&breaker);
+
+        *now.lock().unwrap() += Duration::from_secs(29);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        *now.lock().unwrap() += Duration::from_secs(1);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let _probe = breaker.try_acquire().unwrap();
+        assert!(matches!(
+            breaker.try_acquire(),
+            Err(HashGateError::CircuitOpen)
+        ));
+    }
+
+    #[test]
+    fn closes_when_the_probe_succeeds() {
+        let (breaker, now) = breaker();
+        open(&breaker);
+        *now.lock().unwrap() += Duration::from_secs(30);
+
+        send(&breaker, true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn reopens_when_the_probe_fails() {
+        let (breaker, now) = breaker();
+        open(&breaker);
+        *now.lock().unwrap() += Duration::from_secs(30);
+
+        send(&breaker, false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn dropped_probes_are_given_back() {
+        let (breaker, now) = breaker();
+        open(&breaker);
+        *now.lock().unwrap() += Duration::from_secs(30);
+
+        drop(breaker.try_acquire().unwrap());
+        assert!(breaker.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn recovers_from_a_poisoned_lock() {
+        let (breaker, _) = breaker();
+        open(&breaker);
+
+        let poisoner = breaker.clone();
+        let _ = std::thread::spawn(move || {
+            let _state = poisoner.state.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.try_acquire(),
+            Err(HashGateError::CircuitOpen)
+        ));
+    }
+}
@@ -1,13 +1,44 @@
+#[cfg(feature = "fixtures")]
+use crate::fixtures::{Replayer, Transport};
+#[cfg(feature = "breach")]
+use crate::operations::password_policy::breach::BreachSource;
+#[cfg(feature = "metrics")]
+use crate::telemetry;
 use crate::{
+    circuit_breaker::CircuitBreaker,
     config::HashGateConfig,
     error::HashGateError,
+    interceptor::{Interceptor, RequestContext},
+    operations::{lockout::LoginThrottle, password_policy::PasswordPolicy},
     types::{requests::ClientAuthReq, responses::AuthResponse},
 };
-use reqwest::{header, Response};
+use reqwest::{
+    header::{self, HeaderName},
+    RequestBuilder, Response,
+};
 use serde::Serialize;
-use std::str::FromStr;
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+use std::{future::Future, str::FromStr, sync::Arc};
 use uuid::Uuid;
 
+/// The header every request's id is sent in.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The header idempotency keys are sent in.
+pub const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+#[derive(Debug, Clone)]
+/// The id a response was requested with, stored in the response extensions.
+struct RequestId(String);
+
+/// Get the `X-Request-Id` a response from HashGate was requested with.
+pub(crate) fn request_id(resp: &Response) -> Option<String> {
+    resp.extensions()
+        .get::<RequestId>()
+        .map(|RequestId(request_id)| request_id.clone())
+}
+
 #[derive(Debug, Clone)]
 /// A client for interacting with your HashGate user pool.
 pub struct HashGateClient {
@@ -15,6 +46,16 @@ pub struct HashGateClient {
     client_secret: String,
     token: Option<String>,
     url_base: String,
+    password_policy: Option<PasswordPolicy>,
+    require_breach_check: bool,
+    login_throttle: Option<LoginThrottle>,
+    #[cfg(feature = "breach")]
+    breach_source: Option<Arc<dyn BreachSource>>,
+    #[cfg(feature = "fixtures")]
+    transport: Option<Transport>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    circuit_breaker: Option<CircuitBreaker>,
+    request_id: Option<String>,
     pub req_client: reqwest::Client,
 }
 impl HashGateClient {
@@ -27,6 +68,16 @@ impl HashGateClient {
             client_secret: config.client_secret,
             token: None,
             url_base: String::from("https://api.hash-gate.com/"),
+            password_policy: None,
+            require_breach_check: false,
+            login_throttle: None,
+            #[cfg(feature = "breach")]
+            breach_source: None,
+            #[cfg(feature = "fixtures")]
+            transport: None,
+            interceptors: Vec::new(),
+            circuit_breaker: None,
+            request_id: None,
             req_client: reqwest::Client::new(),
         };
 
@@ -35,6 +86,137 @@ impl HashGateClient {
         Ok(client)
     }
 
+    /// Create a `HashGateClient` that serves recorded exchanges
+    /// instead of talking to HashGate, for running tests offline.
+    #[cfg(feature = "fixtures")]
+    pub fn replaying(replayer: Replayer) -> HashGateClient {
+        HashGateClient {
+            client_id: Uuid::nil(),
+            client_secret: String::new(),
+            token: Some(String::from("replay")),
+            url_base: String::from("https://api.hash-gate.com/"),
+            password_policy: None,
+            require_breach_check: false,
+            login_throttle: None,
+            #[cfg(feature = "breach")]
+            breach_source: None,
+            transport: Some(Transport::Replay(replayer)),
+            interceptors: Vec::new(),
+            circuit_breaker: None,
+            request_id: None,
+            req_client: reqwest::Client::new(),
+        }
+    }
+
+    /// Set how requests are sent, to record or replay exchanges with HashGate.
+    #[cfg(feature = "fixtures")]
+    pub fn set_transport(&mut self, transport: Option<Transport>) {
+        self.transport = transport;
+    }
+
+    /// Get how requests are sent, if not directly to HashGate.
+    #[cfg(feature = "fixtures")]
+    pub fn transport(&self) -> Option<&Transport> {
+        self.transport.as_ref()
+    }
+
+    /// Set the password policy to enforce locally before sending new passwords.
+    pub fn set_password_policy(&mut self, password_policy: Option<PasswordPolicy>) {
+        self.password_policy = password_policy;
+    }
+
+    /// Get the password policy enforced locally, if any.
+    pub fn password_policy(&self) -> Option<&PasswordPolicy> {
+        self.password_policy.as_ref()
+    }
+
+    /// Set the source to check for breached passwords, enforced when
+    /// the password policy has `reject_breached` set.
+    #[cfg(feature = "breach")]
+    pub fn set_breach_source(&mut self, breach_source: Option<Arc<dyn BreachSource>>) {
+        self.breach_source = breach_source;
+    }
+
+    /// Get the source used to check for breached passwords, if any.
+    #[cfg(feature = "breach")]
+    pub fn breach_source(&self) -> Option<&Arc<dyn BreachSource>> {
+        self.breach_source.as_ref()
+    }
+
+    /// Set whether new passwords are rejected with `HashGateError::BreachCheck` when the
+    /// password policy has `reject_breached` set but no breach source is set.
+    ///
+    /// NOTE: Off by default, the breach rule is skipped without a breach source.
+    pub fn set_require_breach_check(&mut self, require_breach_check: bool) {
+        self.require_breach_check = require_breach_check;
+    }
+
+    /// Check if new passwords are rejected when the breach rule can't be checked.
+    pub fn require_breach_check(&self) -> bool {
+        self.require_breach_check
+    }
+
+    /// Set the circuit breaker requests go through, failing fast while HashGate is failing.
+    pub fn set_circuit_breaker(&mut self, circuit_breaker: Option<CircuitBreaker>) {
+        self.circuit_breaker = circuit_breaker;
+    }
+
+    /// Get the circuit breaker requests go through, if any (ex: to report its state).
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
+
+    /// Get a copy of the client that sends a fixed `X-Request-Id` (ex: your own
+    /// correlation id), instead of generating a new one for every request.
+    pub fn with_request_id(&self, request_id: &str) -> HashGateClient {
+        let mut client = self.clone();
+        client.request_id = Some(request_id.to_owned());
+        client
+    }
+
+    /// Build the context of a request to an endpoint.
+    fn request_context(
+        &self,
+        method: &'static str,
+        endpoint: &str,
+        idempotency_key: Option<&str>,
+    ) -> RequestContext {
+        RequestContext {
+            method,
+            endpoint: endpoint.to_owned(),
+            request_id: self
+                .request_id
+                .clone()
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            idempotency_key: idempotency_key.map(str::to_owned),
+        }
+    }
+
+    /// Add an interceptor to run around every request, after the ones already added.
+    pub fn add_interceptor(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    /// Remove all interceptors.
+    pub fn clear_interceptors(&mut self) {
+        self.interceptors.clear();
+    }
+
+    /// Get the interceptors run around every request, in order.
+    pub fn interceptors(&self) -> &[Arc<dyn Interceptor>] {
+        &self.interceptors
+    }
+
+    /// Set the local limiter for failed sign in attempts.
+    pub fn set_login_throttle(&mut self, login_throttle: Option<LoginThrottle>) {
+        self.login_throttle = login_throttle;
+    }
+
+    /// Get the local limiter for failed sign in attempts, if any.
+    pub fn login_throttle(&self) -> Option<&LoginThrottle> {
+        self.login_throttle.as_ref()
+    }
+
     /// Try to authenticate the client with HashGate
     /// NOTE: Client tokens live for 4 hours
     async fn try_authenticate(&mut self) -> Result<(), HashGateError> {
@@ -58,16 +240,113 @@ impl HashGateClient {
                 self.token = Some(token);
                 Ok(())
             } else {
-                Err(HashGateError::FailedSignIn)
+                Err(HashGateError::FailedSignIn { request_id: None })
             }
         } else {
-            Err(HashGateError::FailedSignIn)
+            Err(HashGateError::FailedSignIn { request_id: None })
         }
     }
 
     /// Send a request to HashGate API
     /// NOTE: This is seperated kind of as middlewear to handle re-authing the client
+    ///
+    /// NOTE: `request_fn` builds and sends the request itself, so interceptors only see
+    /// the response or error (`Interceptor::on_request()` isn't run) and no `X-Request-Id`
+    /// is sent. Use `HashGateClient::post()` or `HashGateClient::get()` for the full chain.
     pub async fn send_request<F, T>(&mut self, request_fn: F) -> Result<Response, HashGateError>
+    where
+        F: Fn(&str) -> T,
+        T: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        let context = self.request_context("CUSTOM", "custom", None);
+
+        self.send_endpoint_request(&context, request_fn).await
+    }
+
+    /// Build a request, run the interceptors on it, and send it.
+    ///
+    /// NOTE: The request id and idempotency key stay the same when the request is retried.
+    fn send_intercepted(
+        &self,
+        context: &RequestContext,
+        mut request: RequestBuilder,
+    ) -> impl Future<Output = Result<Response, reqwest::Error>> {
+        request = request.header(REQUEST_ID_HEADER, &context.request_id);
+        if let Some(idempotency_key) = &context.idempotency_key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, idempotency_key);
+        }
+
+        let request = request.build().map(|mut request| {
+            for interceptor in &self.interceptors {
+                interceptor.on_request(context, &mut request);
+            }
+            request
+        });
+        let req_client = self.req_client.clone();
+
+        async move { req_client.execute(request?).await }
+    }
+
+    /// Send a request to a HashGate endpoint through the circuit breaker,
+    /// recording metrics and running the interceptors on the outcome.
+    async fn send_endpoint_request<F, T>(
+        &mut self,
+        context: &RequestContext,
+        request_fn: F,
+    ) -> Result<Response, HashGateError>
+    where
+        F: Fn(&str) -> T,
+        T: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+
+        let permit = self
+            .circuit_breaker
+            .as_ref()
+            .map(CircuitBreaker::try_acquire)
+            .transpose();
+
+        // Rejections from an open circuit go through the same metrics and interceptors
+        let result = match permit {
+            Ok(permit) => {
+                let result = self.send_reauthing(context, request_fn).await;
+                if let Some(permit) = permit {
+                    permit.record(
+                        result
+                            .as_ref()
+                            .is_ok_and(|resp| !resp.status().is_server_error()),
+                    );
+                }
+                result
+            }
+            Err(e) => Err(e),
+        };
+
+        #[cfg(feature = "metrics")]
+        telemetry::record_request(
+            context.method,
+            &context.endpoint,
+            result.as_ref().ok().map(|resp| resp.status()),
+            started.elapsed(),
+        );
+
+        for interceptor in &self.interceptors {
+            match &result {
+                Ok(resp) => interceptor.on_response(context, resp),
+                Err(e) => interceptor.on_error(context, e),
+            }
+        }
+
+        result
+    }
+
+    /// Send a request, re-authing the client and retrying it once if the client token expired.
+    async fn send_reauthing<F, T>(
+        &mut self,
+        context: &RequestContext,
+        request_fn: F,
+    ) -> Result<Response, HashGateError>
     where
         F: Fn(&str) -> T,
         T: std::future::Future<Output = Result<Response, reqwest::Error>>,
@@ -77,16 +356,26 @@ impl HashGateClient {
                 // Check if the client gets a 401 unauthorized to try and re auth the client
                 // this happens when auth token expires.
                 if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
-                    println!("80: {:?}", self.token);
-                    self.try_authenticate().await?;
-                    println!("82: {:?}", self.token);
-                    let retry_response = request_fn(self.token.as_mut().unwrap()).await?;
-                    Ok(retry_response)
+                    let reauth = self.try_authenticate().await;
+                    #[cfg(feature = "metrics")]
+                    telemetry::record_reauth(&context.endpoint, reauth.is_ok());
+                    match reauth {
+                        Ok(()) => request_fn(self.token.as_mut().unwrap()).await.map_err(|e| {
+                            HashGateError::RequestFailed {
+                                request_id: context.request_id.clone(),
+                                source: e,
+                            }
+                        }),
+                        Err(e) => Err(e),
+                    }
                 } else {
                     Ok(resp)
                 }
             }
-            Err(e) => Err(HashGateError::Request(e)),
+            Err(e) => Err(HashGateError::RequestFailed {
+                request_id: context.request_id.clone(),
+                source: e,
+            }),
         }
     }
 
@@ -95,21 +384,53 @@ impl HashGateClient {
         &mut self,
         endpoint: &str,
         payload: &T,
+    ) -> Result<Response, HashGateError> {
+        self.post_idempotent(endpoint, payload, None).await
+    }
+
+    /// Send a post request from the client to HashGate, with an
+    /// `Idempotency-Key` that's reused if the request is retried.
+    pub(crate) async fn post_idempotent<T: Serialize>(
+        &mut self,
+        endpoint: &str,
+        payload: &T,
+        idempotency_key: Option<&str>,
     ) -> Result<Response, HashGateError> {
         if self.token.is_some() {
+            let context = self.request_context("POST", endpoint, idempotency_key);
+
+            // Only record or replay needs the body as JSON
+            #[cfg(feature = "fixtures")]
+            let request = self
+                .transport
+                .as_ref()
+                .and_then(|_| serde_json::to_value(payload).ok());
+            #[cfg(feature = "fixtures")]
+            if let Some(Transport::Replay(replayer)) = &self.transport {
+                let resp = replayer.replay("POST", endpoint, request)?;
+                return into_result(resp, &context);
+            }
+
             let url = format!("{}{}", self.url_base, endpoint);
             let resp = self
                 .clone()
-                .send_request(|token| {
-                    self.req_client
+                .send_endpoint_request(&context, |token| {
+                    let request = self
+                        .req_client
                         .post(&url)
                         .json(&payload)
-                        .header(header::AUTHORIZATION, format!("Bearer {token}"))
-                        .send()
+                        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+                    self.send_intercepted(&context, request)
                 })
                 .await?;
 
-            Ok(resp)
+            #[cfg(feature = "fixtures")]
+            if let Some(Transport::Record(recorder)) = &self.transport {
+                let resp = recorder.record("POST", endpoint, request, resp).await?;
+                return into_result(resp, &context);
+            }
+
+            into_result(resp, &context)
         } else {
             Err(HashGateError::NoClientToken)
         }
@@ -118,21 +439,211 @@ impl HashGateClient {
     /// Send a get request from the client to HashGate
     pub async fn get(&mut self, endpoint: &str) -> Result<Response, HashGateError> {
         if self.token.is_some() {
-            let url = format!("{}{}", self.url_base, endpoint);
+            let context = self.request_context("GET", endpoint, None);
 
+            #[cfg(feature = "fixtures")]
+            if let Some(Transport::Replay(replayer)) = &self.transport {
+                let resp = replayer.replay("GET", endpoint, None)?;
+                return into_result(resp, &context);
+            }
+
+            let url = format!("{}{}", self.url_base, endpoint);
             let resp = self
                 .clone()
-                .send_request(|token| {
-                    self.req_client
+                .send_endpoint_request(&context, |token| {
+                    let request = self
+                        .req_client
                         .get(&url)
-                        .header(header::AUTHORIZATION, format!("Bearer {token}"))
-                        .send()
+                        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+                    self.send_intercepted(&context, request)
                 })
                 .await?;
 
-            Ok(resp)
+            #[cfg(feature = "fixtures")]
+            if let Some(Transport::Record(recorder)) = &self.transport {
+                let resp = recorder.record("GET", endpoint, None, resp).await?;
+                return into_result(resp, &context);
+            }
+
+            into_result(resp, &context)
         } else {
             Err(HashGateError::NoClientToken)
         }
     }
 }
+
+/// Store the id a response was requested with in the response extensions,
+/// turning 5xx responses into `HashGateError::ServerUnavailable`.
+fn into_result(mut resp: Response, context: &RequestContext) -> Result<Response, HashGateError> {
+    if resp.status().is_server_error() {
+        return Err(HashGateError::ServerUnavailable {
+            request_id: Some(context.request_id.clone()),
+        });
+    }
+
+    resp.extensions_mut()
+        .insert(RequestId(context.request_id.clone()));
+    Ok(resp)
+}
+
+#[cfg(all(test, feature = "fixtures"))]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_breaker::CircuitState,
+        fixtures::{Exchange, ReplayMode, Replayer},
+    };
+    use futures_util::FutureExt;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    fn sign_in_exchange(status: u16) -> Exchange {
+        Exchange {
+            method: String::from("POST"),
+            endpoint: String::from("user/sign-in"),
+            request: Some(serde_json::json!({
+                "username": "jo",
+                "password": crate::fixtures::SCRUBBED,
+            })),
+            status,
+            response: serde_json::json!({}),
+        }
+    }
+
+    fn sign_in(status: u16) -> HashGateError {
+        let replayer = Replayer::new(vec![sign_in_exchange(status)], ReplayMode::Matching);
+        HashGateClient::replaying(replayer)
+            .authenticate_user(String::from("jo"), String::from("hunter22"))
+            .now_or_never()
+            .unwrap()
+            .unwrap_err()
+    }
+
+    #[test]
+    fn server_errors_are_unavailable() {
+        for status in [500, 502, 503] {
+            let error = sign_in(status);
+            assert!(matches!(error, HashGateError::ServerUnavailable { .. }));
+            assert!(error.is_unavailable());
+            assert!(error.request_id().is_some());
+        }
+    }
+
+    #[test]
+    fn client_errors_are_not_unavailable() {
+        let error = sign_in(401);
+        assert!(matches!(error, HashGateError::FailedSignIn { .. }));
+        assert!(!error.is_unavailable());
+        assert!(error.request_id().is_some());
+    }
+
+    #[derive(Debug, Default)]
+    /// An interceptor remembering the errors it saw.
+    struct SeenErrors(Mutex<Vec<String>>);
+    impl Interceptor for Arc<SeenErrors> {
+        fn on_error(&self, _context: &RequestContext, error: &HashGateError) {
+            self.0.lock().unwrap().push(error.to_string());
+        }
+    }
+
+    /// A client sending every request through a circuit opening after 2 failures.
+    fn breaking_client(seen_errors: &Arc<SeenErrors>) -> HashGateClient {
+        let mut client = HashGateClient::replaying(Replayer::new(Vec::new(), ReplayMode::InOrder));
+        client.set_transport(None);
+        client.set_circuit_breaker(Some(
+            CircuitBreaker::new(0.5, Duration::from_secs(30), 1)
+                .window_size(2)
+                .minimum_requests(2),
+        ));
+        client.add_interceptor(seen_errors.clone());
+        client
+    }
+
+    /// Send a request HashGate answers with a 500, counting the requests that got sent.
+    fn send_failing(client: &mut HashGateClient, sent: &AtomicUsize) -> Result<u16, HashGateError> {
+        client
+            .send_request(|_token| {
+                sent.fetch_add(1, Ordering::SeqCst);
+                let resp = http::Response::builder().status(500).body("").unwrap();
+                futures_util::future::ready(Ok(Response::from(resp)))
+            })
+            .now_or_never()
+            .unwrap()
+            .map(|resp| resp.status().as_u16())
+    }
+
+    #[test]
+    fn server_errors_open_the_circuit() {
+        let seen_errors = Arc::new(SeenErrors::default());
+        let mut client = breaking_client(&seen_errors);
+        let sent = AtomicUsize::new(0);
+
+        assert_eq!(send_failing(&mut client, &sent).unwrap(), 500);
+        assert_eq!(send_failing(&mut client, &sent).unwrap(), 500);
+        assert_eq!(
+            client.circuit_breaker().unwrap().state(),
+            CircuitState::Open
+        );
+
+        assert!(matches!(
+            send_failing(&mut client, &sent),
+            Err(HashGateError::CircuitOpen)
+        ));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            *seen_errors.0.lock().unwrap(),
+            vec![HashGateError::CircuitOpen.to_string()]
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn circuit_open_rejections_are_recorded() {
+        use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, SharedString, Unit};
+
+        #[derive(Default)]
+        /// A recorder remembering the status label of every request counted.
+        struct RequestStatuses(Mutex<Vec<String>>);
+        impl metrics::Recorder for RequestStatuses {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                if key.name() == telemetry::REQUESTS_TOTAL {
+                    let status = key.labels().find(|label| label.key() == "status");
+                    if let Some(status) = status {
+                        self.0.lock().unwrap().push(status.value().to_owned());
+                    }
+                }
+                Counter::noop()
+            }
+
+            fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::noop()
+            }
+
+            fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        let recorder = RequestStatuses::default();
+        let seen_errors = Arc::new(SeenErrors::default());
+        let mut client = breaking_client(&seen_errors);
+        let sent = AtomicUsize::new(0);
+
+        metrics::with_local_recorder(&recorder, || {
+            for _ in 0..3 {
+                let _ = send_failing(&mut client, &sent);
+            }
+        });
+
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["5xx", "5xx", "error"]);
+    }
+}
@@ -1,35 +1,170 @@
+use crate::operations::password_policy::PasswordViolation;
+use chrono::NaiveDateTime;
+use std::time::Duration;
+
 #[derive(Debug)]
 /// Hash Gate Errors
+///
+/// Errors from a HashGate response carry the `X-Request-Id` of the request,
+/// get it with `HashGateError::request_id()`.
+///
+/// NOTE: Requests that get no response are `HashGateError::RequestFailed`, not
+/// `HashGateError::Request`, match both or use `HashGateError::is_request_error()`
+/// (see the 0.2.0 changelog).
 pub enum HashGateError {
-    FailedSignIn,
+    FailedSignIn {
+        request_id: Option<String>,
+    },
     FailedConfig,
     NoClientToken,
-    UserNotFound,
-    ServerError,
+    UserNotFound {
+        request_id: Option<String>,
+    },
+    ServerError {
+        request_id: Option<String>,
+    },
     Uuid(uuid::Error),
     Request(reqwest::Error),
-    CouldNotSetAttribute,
-    UsernameTaken,
+    CouldNotSetAttribute {
+        request_id: Option<String>,
+    },
+    UsernameTaken {
+        request_id: Option<String>,
+    },
+    InvalidMfaCode {
+        request_id: Option<String>,
+    },
+    TooManyAttempts {
+        request_id: Option<String>,
+    },
+    SessionExpired,
+    MissingEmail,
+    MissingPhoneNumber,
+    Delivery {
+        reason: String,
+        request_id: Option<String>,
+    },
+    WeakPassword(Vec<PasswordViolation>),
+    BreachCheck(String),
+    InvalidUsername(String),
+    InvalidEmail(String),
+    AccountLocked {
+        until: Option<NaiveDateTime>,
+    },
+    RateLimited {
+        retry_after: Duration,
+    },
+    InvalidWebhook(String),
+    WebhookNotFound {
+        request_id: Option<String>,
+    },
+    Fixture(String),
+    RequestFailed {
+        request_id: String,
+        source: reqwest::Error,
+    },
+    CircuitOpen,
+    ServerUnavailable {
+        request_id: Option<String>,
+    },
+    MalformedWebhook(String),
 }
 /// Implement display trait for `HashGateError`
 impl std::fmt::Display for HashGateError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::FailedSignIn => write!(f, "Error: Sign In Attempt Failed"),
+            Self::FailedSignIn { .. } => write!(f, "Error: Sign In Attempt Failed"),
             Self::FailedConfig => write!(f, "Error: Environment Variables Not Set"),
             Self::NoClientToken => write!(f, "Error: HashGate Client Missing Auth Token"),
-            Self::UserNotFound => write!(f, "Error: User Not Found"),
-            Self::CouldNotSetAttribute => write!(f, "Error: Could Not Set User Attribute"),
-            Self::UsernameTaken => write!(f, "Error: That Username Or Email Is Already In Use"),
-            Self::ServerError => write!(
+            Self::UserNotFound { .. } => write!(f, "Error: User Not Found"),
+            Self::CouldNotSetAttribute { .. } => write!(f, "Error: Could Not Set User Attribute"),
+            Self::UsernameTaken { .. } => {
+                write!(f, "Error: That Username Or Email Is Already In Use")
+            }
+            Self::InvalidMfaCode { .. } => write!(f, "Error: Invalid Or Expired MFA Code"),
+            Self::TooManyAttempts { .. } => write!(f, "Error: Too Many Attempts For This Session"),
+            Self::SessionExpired => write!(f, "Error: Session Has Expired"),
+            Self::MissingEmail => write!(f, "Error: User Has No Email To Deliver To"),
+            Self::MissingPhoneNumber => write!(f, "Error: User Has No Phone Number To Deliver To"),
+            Self::Delivery { reason, .. } => write!(f, "Error: Could Not Deliver Code: {reason}"),
+            Self::BreachCheck(e) => write!(f, "Error: Could Not Check For Breached Password: {e}"),
+            Self::InvalidUsername(reason) => write!(f, "Error: Invalid Username: {reason}"),
+            Self::InvalidEmail(reason) => write!(f, "Error: Invalid Email: {reason}"),
+            Self::AccountLocked { until: Some(until) } => {
+                write!(f, "Error: Account Is Locked Until {until} UTC")
+            }
+            Self::AccountLocked { until: None } => write!(f, "Error: Account Is Locked"),
+            Self::InvalidWebhook(reason) => write!(f, "Error: Invalid Webhook: {reason}"),
+            Self::MalformedWebhook(reason) => write!(f, "Error: Malformed Webhook: {reason}"),
+            Self::WebhookNotFound { .. } => write!(f, "Error: Webhook Not Found"),
+            Self::Fixture(e) => write!(f, "Error: HTTP Fixture: {e}"),
+            Self::CircuitOpen => write!(f, "Error: HashGate Is Unavailable, Circuit Is Open"),
+            Self::ServerUnavailable { .. } => write!(f, "Error: HashGate Is Unavailable"),
+            Self::RequestFailed { request_id, source } => {
+                write!(f, "Error: Request {request_id} Failed: {source:?}")
+            }
+            Self::RateLimited { retry_after } => write!(
+                f,
+                "Error: Too Many Sign In Attempts, Retry In {}s",
+                retry_after.as_secs()
+            ),
+            Self::WeakPassword(violations) => {
+                write!(f, "Error: Password Does Not Meet The Password Policy")?;
+                for violation in violations {
+                    write!(f, ", {violation}")?;
+                }
+                Ok(())
+            }
+            Self::ServerError { .. } => write!(
                 f,
                 "Error: HashGate Server Ran Into Issues With Your Request"
             ),
             Self::Uuid(e) => write!(f, "{e:?}"),
             Self::Request(e) => write!(f, "{e:?}"),
+        }?;
+
+        match self {
+            Self::RequestFailed { .. } => Ok(()),
+            _ => match self.request_id() {
+                Some(request_id) => write!(f, " (Request {request_id})"),
+                None => Ok(()),
+            },
         }
     }
 }
+impl HashGateError {
+    /// Get the `X-Request-Id` of the request that failed, if the error came from sending one.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::RequestFailed { request_id, .. } => Some(request_id),
+            Self::FailedSignIn { request_id }
+            | Self::UserNotFound { request_id }
+            | Self::ServerError { request_id }
+            | Self::CouldNotSetAttribute { request_id }
+            | Self::UsernameTaken { request_id }
+            | Self::InvalidMfaCode { request_id }
+            | Self::TooManyAttempts { request_id }
+            | Self::WebhookNotFound { request_id }
+            | Self::ServerUnavailable { request_id }
+            | Self::Delivery { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Check if the error came from not reaching HashGate (ex: a timeout, or an open circuit).
+    pub fn is_request_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Request(_) | Self::RequestFailed { .. } | Self::CircuitOpen
+        )
+    }
+
+    /// Check if the error came from HashGate being unreachable or failing (ex: a timeout,
+    /// a 5xx response, or an open circuit), rather than HashGate rejecting the request.
+    pub fn is_unavailable(&self) -> bool {
+        self.is_request_error() || matches!(self, Self::ServerUnavailable { .. })
+    }
+}
 /// Implement error conversion (`uuid::Error` -> `HashGateError`)
 impl From<uuid::Error> for HashGateError {
     fn from(err: uuid::Error) -> HashGateError {
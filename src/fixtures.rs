@@ -0,0 +1,300 @@
+use crate::error::HashGateError;
+use reqwest::{header, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// What scrubbed values are replaced with.
+pub const SCRUBBED: &str = "[SCRUBBED]";
+
+/// Keys holding passwords, secrets, tokens or codes, scrubbed wherever they appear.
+const SCRUBBED_KEYS: [&str; 10] = [
+    "password",
+    "newPassword",
+    "clientSecret",
+    "secret",
+    "provisioningUri",
+    "token",
+    "code",
+    "verificationCode",
+    "recoveryCodes",
+    "magicLink",
+];
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// A request made to HashGate along with its response.
+pub struct Exchange {
+    /// The HTTP method of the request (ex: `POST`).
+    pub method: String,
+
+    /// The endpoint of the request (ex: `user/sign-in`).
+    pub endpoint: String,
+
+    /// The scrubbed JSON body of the request, if it had one.
+    pub request: Option<Value>,
+
+    /// The status code of the response.
+    pub status: u16,
+
+    /// The scrubbed JSON body of the response, non JSON bodies are kept as a string.
+    pub response: Value,
+}
+
+#[derive(Debug, Clone)]
+/// How HashGate requests are sent.
+pub enum Transport {
+    /// Send requests to HashGate, recording every exchange.
+    Record(Recorder),
+
+    /// Serve recorded exchanges instead of sending requests to HashGate.
+    Replay(Replayer),
+}
+
+#[derive(Debug, Clone)]
+/// Records exchanges as JSON lines, with secrets and tokens scrubbed.
+///
+/// NOTE: Clones share the same file.
+pub struct Recorder {
+    file: Arc<Mutex<File>>,
+}
+impl Recorder {
+    /// Create a `Recorder` appending to a JSONL file, creating it if needed.
+    pub fn create(path: impl AsRef<Path>) -> Result<Recorder, HashGateError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| HashGateError::Fixture(e.to_string()))?;
+
+        Ok(Recorder {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Record an exchange, giving back an equivalent response to hand to the caller.
+    pub(crate) async fn record(
+        &self,
+        method: &str,
+        endpoint: &str,
+        request: Option<Value>,
+        resp: Response,
+    ) -> Result<Response, HashGateError> {
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?;
+
+        let response = serde_json::from_slice(&body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+        let exchange = Exchange {
+            method: method.to_owned(),
+            endpoint: endpoint.to_owned(),
+            request: request.map(scrub),
+            status: status.as_u16(),
+            response: scrub(response),
+        };
+
+        let line =
+            serde_json::to_string(&exchange).map_err(|e| HashGateError::Fixture(e.to_string()))?;
+        if let Ok(mut file) = self.file.lock() {
+            writeln!(file, "{line}").map_err(|e| HashGateError::Fixture(e.to_string()))?;
+        }
+
+        let mut replayed = http::Response::new(body);
+        *replayed.status_mut() = status;
+        *replayed.headers_mut() = headers;
+
+        Ok(Response::from(replayed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How a `Replayer` picks the exchange for a request.
+pub enum ReplayMode {
+    /// Exchanges are served in the order they were recorded,
+    /// the method and endpoint of each request must match the next exchange.
+    InOrder,
+
+    /// The first unused exchange with the same method,
+    /// endpoint and scrubbed request body is served.
+    Matching,
+}
+
+#[derive(Debug, Clone)]
+/// Serves recorded exchanges back, so tests can run without HashGate.
+///
+/// NOTE: Clones share the remaining exchanges.
+pub struct Replayer {
+    mode: ReplayMode,
+    exchanges: Arc<Mutex<VecDeque<Exchange>>>,
+}
+impl Replayer {
+    /// Create a `Replayer` from exchanges.
+    pub fn new(exchanges: Vec<Exchange>, mode: ReplayMode) -> Replayer {
+        Replayer {
+            mode,
+            exchanges: Arc::new(Mutex::new(exchanges.into())),
+        }
+    }
+
+    /// Load a `Replayer` from a JSONL file written by a `Recorder`.
+    pub fn from_file(path: impl AsRef<Path>, mode: ReplayMode) -> Result<Replayer, HashGateError> {
+        let file = File::open(path).map_err(|e| HashGateError::Fixture(e.to_string()))?;
+
+        let mut exchanges = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| HashGateError::Fixture(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let exchange = serde_json::from_str::<Exchange>(&line)
+                .map_err(|e| HashGateError::Fixture(e.to_string()))?;
+            exchanges.push(exchange);
+        }
+
+        Ok(Replayer::new(exchanges, mode))
+    }
+
+    /// Get the number of exchanges that haven't been served yet.
+    pub fn remaining(&self) -> usize {
+        self.exchanges
+            .lock()
+            .map(|exchanges| exchanges.len())
+            .unwrap_or_default()
+    }
+
+    /// Serve the recorded response for a request.
+    pub(crate) fn replay(
+        &self,
+        method: &str,
+        endpoint: &str,
+        request: Option<Value>,
+    ) -> Result<Response, HashGateError> {
+        let no_fixture = || HashGateError::Fixture(format!("No Fixture For {method} {endpoint}"));
+
+        let Ok(mut exchanges) = self.exchanges.lock() else {
+            return Err(no_fixture());
+        };
+
+        let is_match =
+            |exchange: &Exchange| exchange.method == method && exchange.endpoint == endpoint;
+        let exchange = match self.mode {
+            ReplayMode::InOrder => {
+                if !exchanges.front().is_some_and(is_match) {
+                    return Err(no_fixture());
+                }
+                exchanges.pop_front()
+            }
+            ReplayMode::Matching => {
+                let request = request.map(scrub);
+                exchanges
+                    .iter()
+                    .position(|exchange| is_match(exchange) && exchange.request == request)
+                    .and_then(|index| exchanges.remove(index))
+            }
+        };
+        let exchange = exchange.ok_or_else(no_fixture)?;
+
+        let body = match exchange.response {
+            Value::String(body) => body.into_bytes(),
+            body => body.to_string().into_bytes(),
+        };
+
+        let mut replayed = http::Response::new(body);
+        *replayed.status_mut() = reqwest::StatusCode::from_u16(exchange.status)
+            .map_err(|e| HashGateError::Fixture(e.to_string()))?;
+        replayed.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+
+        Ok(Response::from(replayed))
+    }
+}
+
+/// Replace passwords, secrets, tokens and codes in a JSON value.
+fn scrub(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| {
+                    if SCRUBBED_KEYS.contains(&key.as_str()) {
+                        (key, scrub_scalars(value))
+                    } else {
+                        (key, scrub(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(scrub).collect()),
+        value => value,
+    }
+}
+
+/// Replace every string, number and bool in a JSON value, keeping its shape
+/// and nulls so optional fields of scrubbed exchanges still deserialize.
+fn scrub_scalars(value: Value) -> Value {
+    match value {
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => Value::String(SCRUBBED.to_owned()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, scrub_scalars(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(scrub_scalars).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn scrubs_only_listed_keys() {
+        let scrubbed = scrub(json!({
+            "username": "alice",
+            "password": "hunter22",
+            "passwordPolicy": { "minLength": 8, "bannedPasswords": ["password"] },
+            "user": { "token": "abc" },
+        }));
+
+        assert_eq!(
+            scrubbed,
+            json!({
+                "username": "alice",
+                "password": SCRUBBED,
+                "passwordPolicy": { "minLength": 8, "bannedPasswords": ["password"] },
+                "user": { "token": SCRUBBED },
+            })
+        );
+    }
+
+    #[test]
+    fn scrubs_every_scalar_of_listed_keys() {
+        let scrubbed = scrub(json!({
+            "token": null,
+            "recoveryCodes": ["a", "b"],
+            "code": 123456,
+        }));
+
+        assert_eq!(
+            scrubbed,
+            json!({
+                "token": null,
+                "recoveryCodes": [SCRUBBED, SCRUBBED],
+                "code": SCRUBBED,
+            })
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{client::HashGateClient, integrations::parse_bearer_token, operations::user::User};
+use actix_web::{
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::{ErrorInternalServerError, ErrorServiceUnavailable, ErrorUnauthorized},
+    guard::{fn_guard, Guard, GuardContext},
+    http::header,
+    middleware::Next,
+    web, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use std::{future::Future, pin::Pin};
+
+#[derive(Debug, Clone)]
+/// The result of authenticating a request, stored in the request extensions.
+enum AuthState {
+    Authenticated(Box<User>),
+    MissingToken,
+    InvalidToken,
+    Unavailable,
+}
+impl AuthState {
+    /// Turn the state into the `User`, or the error to reject the request with.
+    fn into_user(self) -> Result<User, Error> {
+        match self {
+            AuthState::Authenticated(user) => Ok(*user),
+            AuthState::MissingToken => Err(ErrorUnauthorized("Missing Auth Token")),
+            AuthState::InvalidToken => Err(ErrorUnauthorized("Invalid Auth Token")),
+            AuthState::Unavailable => Err(ErrorServiceUnavailable("Auth Is Unavailable")),
+        }
+    }
+}
+
+/// Pull the bearer token out of the request's `Authorization` header.
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer_token)
+        .map(str::to_owned)
+}
+
+/// Authenticate a request with the `HashGateClient` registered as app data.
+async fn try_authenticate(req: &HttpRequest) -> Result<AuthState, Error> {
+    let mut client = req
+        .app_data::<web::Data<HashGateClient>>()
+        .map(|client| client.get_ref().clone())
+        .ok_or_else(|| ErrorInternalServerError("HashGateClient Is Not Configured"))?;
+
+    let state = match bearer_token(req) {
+        Some(token) => match User::try_from_token(&token, &mut client).await {
+            Ok(user) => AuthState::Authenticated(Box::new(user)),
+            Err(e) if e.is_unavailable() => AuthState::Unavailable,
+            Err(_) => AuthState::InvalidToken,
+        },
+        None => AuthState::MissingToken,
+    };
+
+    Ok(state)
+}
+
+/// Middleware that validates the bearer token once per request and
+/// stores the result in the request extensions, for the `User`
+/// extractor and the `verified`, `in_group` and `has_permission` guards.
+///
+/// NOTE: The `HashGateClient` must be registered as app data:
+///
+/// ```ignore
+/// App::new()
+///     .app_data(web::Data::new(client))
+///     .wrap(actix_web::middleware::from_fn(authenticate))
+/// ```
+pub async fn authenticate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let state = try_authenticate(req.request()).await?;
+    req.extensions_mut().insert(state);
+
+    next.call(req).await
+}
+
+/// Extract the authenticated `User`, rejecting with a 401 otherwise.
+///
+/// NOTE: Use `Option<User>` for routes where authentication is optional.
+/// Without the `authenticate` middleware the token is validated on extraction.
+impl FromRequest for User {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<User, Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let state = req.extensions().get::<AuthState>().cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            match state {
+                Some(state) => state.into_user(),
+                None => try_authenticate(&req).await?.into_user(),
+            }
+        })
+    }
+}
+
+/// Look up the authenticated `User` for a guard.
+fn guard_user(ctx: &GuardContext<'_>, check: impl Fn(&User) -> bool) -> bool {
+    match ctx.req_data().get::<AuthState>() {
+        Some(AuthState::Authenticated(user)) => check(user),
+        _ => false,
+    }
+}
+
+/// Guard requiring the authenticated `User` to be verified.
+///
+/// NOTE: Guards only match routes, so requests failing the guard
+/// fall through to the next route (or a 404), not a 403.
+pub fn verified() -> impl Guard {
+    fn_guard(|ctx| guard_user(ctx, |user| user.is_verified))
+}
+
+/// Guard requiring the authenticated `User` to belong to a group.
+pub fn in_group(group_id: i32) -> impl Guard {
+    fn_guard(move |ctx| guard_user(ctx, |user| user.in_group(group_id)))
+}
+
+/// Guard requiring the authenticated `User` to have a permission (ex: `billing:write`).
+pub fn has_permission(permission: &str) -> impl Guard {
+    let permission = permission.to_owned();
+    fn_guard(move |ctx| guard_user(ctx, |user| user.has_permission(&permission)))
+}
@@ -0,0 +1,159 @@
+use crate::{client::HashGateClient, integrations::parse_bearer_token, operations::user::User};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+#[derive(Debug, Clone)]
+/// The result of authenticating a request, stored in the request extensions.
+enum AuthState {
+    Authenticated(Box<User>),
+    MissingToken,
+    InvalidToken,
+    Unavailable,
+}
+
+/// Middleware that validates the bearer token once per request and
+/// stores the resulting `User` in the request extensions.
+///
+/// NOTE: This must be layered on any routes using `AuthUser`, `MaybeAuthUser` or `VerifiedUser`:
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/me", get(me))
+///     .layer(axum::middleware::from_fn_with_state(client, authenticate));
+/// ```
+pub async fn authenticate(
+    State(client): State<HashGateClient>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_bearer_token)
+        .map(str::to_owned);
+
+    let state = match token {
+        Some(token) => {
+            let mut client = client;
+            match User::try_from_token(&token, &mut client).await {
+                Ok(user) => AuthState::Authenticated(Box::new(user)),
+                Err(e) if e.is_unavailable() => AuthState::Unavailable,
+                Err(_) => AuthState::InvalidToken,
+            }
+        }
+        None => AuthState::MissingToken,
+    };
+
+    req.extensions_mut().insert(state);
+    next.run(req).await
+}
+
+#[derive(Debug)]
+/// Why a request was rejected by one of the HashGate extractors.
+pub enum AuthRejection {
+    /// No bearer token was sent (401).
+    MissingToken,
+
+    /// The bearer token isn't valid (401).
+    InvalidToken,
+
+    /// The user isn't verified (403).
+    Unverified,
+
+    /// HashGate couldn't be reached or is failing (ex: a 5xx) to validate the token (503).
+    Unavailable,
+
+    /// The `authenticate` middleware isn't layered on the route (500).
+    MissingMiddleware,
+}
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::MissingToken => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                "Missing Auth Token",
+            )
+                .into_response(),
+            Self::InvalidToken => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer error=\"invalid_token\"")],
+                "Invalid Auth Token",
+            )
+                .into_response(),
+            Self::Unverified => (StatusCode::FORBIDDEN, "User Is Not Verified").into_response(),
+            Self::Unavailable => {
+                (StatusCode::SERVICE_UNAVAILABLE, "Auth Is Unavailable").into_response()
+            }
+            Self::MissingMiddleware => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "HashGate Middleware Is Not Configured",
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Look up the `AuthState` left behind by the `authenticate` middleware.
+fn auth_state(parts: &Parts) -> Result<&AuthState, AuthRejection> {
+    parts
+        .extensions
+        .get::<AuthState>()
+        .ok_or(AuthRejection::MissingMiddleware)
+}
+
+#[derive(Debug, Clone)]
+/// Extractor for an authenticated `User`, rejecting with a 401 otherwise.
+pub struct AuthUser(pub User);
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match auth_state(parts)? {
+            AuthState::Authenticated(user) => Ok(AuthUser(*user.clone())),
+            AuthState::MissingToken => Err(AuthRejection::MissingToken),
+            AuthState::InvalidToken => Err(AuthRejection::InvalidToken),
+            AuthState::Unavailable => Err(AuthRejection::Unavailable),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Extractor for an optionally authenticated `User`.
+///
+/// NOTE: Missing or invalid tokens are treated as anonymous requests.
+pub struct MaybeAuthUser(pub Option<User>);
+impl<S: Send + Sync> FromRequestParts<S> for MaybeAuthUser {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match auth_state(parts)? {
+            AuthState::Authenticated(user) => Ok(MaybeAuthUser(Some(*user.clone()))),
+            AuthState::MissingToken | AuthState::InvalidToken => Ok(MaybeAuthUser(None)),
+            AuthState::Unavailable => Err(AuthRejection::Unavailable),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Extractor for an authenticated and verified `User`, rejecting
+/// with a 401 if not authenticated and a 403 if not verified.
+pub struct VerifiedUser(pub User);
+impl<S: Send + Sync> FromRequestParts<S> for VerifiedUser {
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthUser(user) = AuthUser::from_request_parts(parts, state).await?;
+
+        if user.is_verified {
+            Ok(VerifiedUser(user))
+        } else {
+            Err(AuthRejection::Unverified)
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{client::HashGateClient, error::HashGateError, operations::user::User};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "tower")]
+pub mod tower;
+
+/// Pull the token out of an `Authorization: Bearer <token>` header value.
+pub fn parse_bearer_token(value: &str) -> Option<&str> {
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+
+    if scheme.eq_ignore_ascii_case("bearer") && !token.is_empty() {
+        Some(token)
+    } else {
+        None
+    }
+}
+
+/// Resolve a user auth token into a `User`.
+pub trait TokenResolver {
+    /// Try to resolve a token into the `User` it belongs to.
+    fn resolve(&self, token: &str) -> impl Future<Output = Result<User, HashGateError>> + Send;
+}
+
+/// Resolve tokens remotely through HashGate.
+impl TokenResolver for HashGateClient {
+    async fn resolve(&self, token: &str) -> Result<User, HashGateError> {
+        let mut client = self.clone();
+        User::try_from_token(token, &mut client).await
+    }
+}
+
+#[derive(Clone)]
+/// A `TokenResolver` that caches resolved users for a fixed amount of time.
+///
+/// NOTE: A cached user can outlive a token revoked in HashGate by up to the ttl
+/// (plus the max staleness, while HashGate can't be reached).
+pub struct CachedTokenResolver<R> {
+    inner: R,
+    ttl: Duration,
+    max_stale: Duration,
+    cache: Arc<Mutex<HashMap<String, (User, Instant)>>>,
+}
+impl<R> CachedTokenResolver<R> {
+    /// Wrap a `TokenResolver` with a cache.
+    pub fn new(inner: R, ttl: Duration) -> CachedTokenResolver<R> {
+        CachedTokenResolver {
+            inner,
+            ttl,
+            max_stale: Duration::ZERO,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Serve expired users for up to `max_stale` past the ttl when HashGate
+    /// can't be reached or is failing (ex: a 5xx, or the client's circuit breaker is open).
+    ///
+    /// Tokens HashGate rejected are removed from the cache and never served stale.
+    pub fn serve_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = max_stale;
+        self
+    }
+
+    /// Remove a token from the cache (ex: after a user signs out).
+    pub fn invalidate(&self, token: &str) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.remove(token);
+        }
+    }
+
+    /// Get a cached user for a token, if it was cached within `max_age`.
+    fn cached(&self, token: &str, max_age: Duration) -> Option<User> {
+        let mut cache = self.cache.lock().ok()?;
+        match cache.get(token) {
+            Some((user, cached_at)) if cached_at.elapsed() < max_age => Some(user.clone()),
+            Some((_, cached_at)) if cached_at.elapsed() >= self.ttl + self.max_stale => {
+                cache.remove(token);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+impl<R: TokenResolver + Sync> TokenResolver for CachedTokenResolver<R> {
+    async fn resolve(&self, token: &str) -> Result<User, HashGateError> {
+        let cached = self.cached(token, self.ttl);
+        #[cfg(feature = "metrics")]
+        crate::telemetry::record_cache_lookup("token", cached.is_some());
+        if let Some(user) = cached {
+            return Ok(user);
+        }
+
+        let user = match self.inner.resolve(token).await {
+            Ok(user) => user,
+            Err(e) if e.is_unavailable() => {
+                return self.cached(token, self.ttl + self.max_stale).ok_or(e);
+            }
+            // Only a rejected token is removed, other errors say nothing about the token
+            Err(e @ (HashGateError::UserNotFound { .. } | HashGateError::ServerError { .. })) => {
+                self.invalidate(token);
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+        if let Ok(mut cache) = self.cache.lock() {
+            // Drop expired entries so the cache doesn't grow with every token seen
+            let max_age = self.ttl + self.max_stale;
+            cache.retain(|_, (_, cached_at)| cached_at.elapsed() < max_age);
+            cache.insert(token.to_owned(), (user.clone(), Instant::now()));
+        }
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    #[derive(Clone)]
+    /// A `TokenResolver` giving back queued results.
+    struct QueuedResolver(Arc<Mutex<Vec<Result<User, HashGateError>>>>);
+    impl TokenResolver for QueuedResolver {
+        async fn resolve(&self, _token: &str) -> Result<User, HashGateError> {
+            self.0.lock().unwrap().remove(0)
+        }
+    }
+
+    pub(super) fn user() -> User {
+        let now = chrono::Utc::now().naive_utc();
+        User {
+            id: uuid::Uuid::new_v4(),
+            username: String::from("alice"),
+            email: None,
+            phone_number: None,
+            is_verified: true,
+            group_id: None,
+            group_ids: Vec::new(),
+            permissions: Vec::new(),
+            locked_until: None,
+            creation_date: now,
+            updated_date: now,
+            custom_attributes: serde_json::json!({}),
+        }
+    }
+
+    fn resolver(results: Vec<Result<User, HashGateError>>) -> CachedTokenResolver<QueuedResolver> {
+        let inner = QueuedResolver(Arc::new(Mutex::new(results)));
+        CachedTokenResolver::new(inner, Duration::ZERO).serve_stale(Duration::from_secs(60))
+    }
+
+    #[test]
+    fn serves_stale_users_while_hashgate_is_failing() {
+        let user = user();
+        let resolver = resolver(vec![
+            Ok(user.clone()),
+            Err(HashGateError::ServerUnavailable { request_id: None }),
+            Err(HashGateError::CircuitOpen),
+        ]);
+
+        for _ in 0..3 {
+            let resolved = resolver.resolve("token").now_or_never().unwrap();
+            assert_eq!(resolved.unwrap().id, user.id);
+        }
+    }
+
+    #[test]
+    fn rejected_tokens_are_not_served_stale() {
+        let resolver = resolver(vec![
+            Ok(user()),
+            Err(HashGateError::UserNotFound { request_id: None }),
+            Err(HashGateError::ServerUnavailable { request_id: None }),
+        ]);
+
+        assert!(resolver.resolve("token").now_or_never().unwrap().is_ok());
+        assert!(matches!(
+            resolver.resolve("token").now_or_never().unwrap(),
+            Err(HashGateError::UserNotFound { .. })
+        ));
+        assert!(matches!(
+            resolver.resolve("token").now_or_never().unwrap(),
+            Err(HashGateError::ServerUnavailable { .. })
+        ));
+    }
+}
@@ -0,0 +1,456 @@
+use crate::integrations::{parse_bearer_token, TokenResolver};
+use http::{header, HeaderName, HeaderValue, Request, Response, StatusCode};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why a request was rejected by the `HashGateAuth` service.
+pub enum Rejection {
+    /// No token was sent.
+    MissingToken,
+
+    /// The token isn't valid.
+    InvalidToken,
+
+    /// HashGate couldn't be reached or is failing (ex: a 5xx) to validate the token.
+    Unavailable,
+}
+impl Rejection {
+    /// The HTTP status code for the rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::MissingToken | Self::InvalidToken => StatusCode::UNAUTHORIZED,
+            Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// The gRPC status code for the rejection (`UNAUTHENTICATED` or `UNAVAILABLE`).
+    pub fn grpc_status(&self) -> u16 {
+        match self {
+            Self::MissingToken | Self::InvalidToken => 16,
+            Self::Unavailable => 14,
+        }
+    }
+
+    /// A short message describing the rejection.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::MissingToken => "Missing Auth Token",
+            Self::InvalidToken => "Invalid Auth Token",
+            Self::Unavailable => "Auth Is Unavailable",
+        }
+    }
+}
+
+/// Render a rejection as a plain HTTP response with an empty body.
+pub fn http_rejection<B: Default>(rejection: Rejection) -> Response<B> {
+    let mut resp = Response::new(B::default());
+    *resp.status_mut() = rejection.status();
+
+    if rejection.status() == StatusCode::UNAUTHORIZED {
+        resp.headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+
+    resp
+}
+
+/// Render a rejection as a trailers-only gRPC response, for tonic services.
+pub fn grpc_rejection<B: Default>(rejection: Rejection) -> Response<B> {
+    let mut resp = Response::new(B::default());
+    let headers = resp.headers_mut();
+
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/grpc"),
+    );
+    headers.insert("grpc-status", HeaderValue::from(rejection.grpc_status()));
+    headers.insert(
+        "grpc-message",
+        HeaderValue::from_static(rejection.message()),
+    );
+
+    resp
+}
+
+/// Configuration shared by the layer and every service it creates.
+struct AuthConfig<B> {
+    token_header: HeaderName,
+    public_paths: Vec<String>,
+    public_prefixes: Vec<String>,
+    render: Arc<dyn Fn(Rejection) -> Response<B> + Send + Sync>,
+}
+impl<B> Clone for AuthConfig<B> {
+    fn clone(&self) -> Self {
+        AuthConfig {
+            token_header: self.token_header.clone(),
+            public_paths: self.public_paths.clone(),
+            public_prefixes: self.public_prefixes.clone(),
+            render: self.render.clone(),
+        }
+    }
+}
+impl<B> AuthConfig<B> {
+    /// Check if a path skips authentication, prefixes only match whole segments.
+    fn is_public(&self, path: &str) -> bool {
+        let Some(path) = normalize_path(path) else {
+            return false;
+        };
+
+        self.public_paths.contains(&path)
+            || self.public_prefixes.iter().any(|prefix| {
+                let prefix = prefix.trim_end_matches('/');
+                path == prefix
+                    || path
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+
+    /// Pull the token out of the configured header value.
+    ///
+    /// NOTE: Custom headers may hold either a bearer token or the raw token.
+    fn extract_token<'a>(&self, value: &'a str) -> Option<&'a str> {
+        match parse_bearer_token(value) {
+            Some(token) => Some(token),
+            None if self.token_header != header::AUTHORIZATION && !value.trim().is_empty() => {
+                Some(value.trim())
+            }
+            None => None,
+        }
+    }
+}
+
+/// Normalize a request path, resolving empty, `.` and `..` segments (including
+/// percent encoded ones) the way routers do before matching it.
+///
+/// Returns `None` for paths that can't be normalized safely (ex: an encoded `/`),
+/// which are never treated as public.
+fn normalize_path(path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        let segment = percent_decode(segment)?;
+        if segment.contains(['/', '\\']) {
+            return None;
+        }
+
+        match segment.as_str() {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Percent decode a path segment, returning `None` if it isn't valid.
+fn percent_decode(segment: &str) -> Option<String> {
+    if !segment.contains('%') {
+        return Some(segment.to_owned());
+    }
+
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = segment.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// A `tower::Layer` that authenticates requests through HashGate, works for
+/// any http based service (ex: hyper, tonic, axum).
+///
+/// Authenticated requests get their `User` inserted into the request extensions.
+pub struct HashGateAuthLayer<R, B> {
+    resolver: R,
+    config: AuthConfig<B>,
+}
+impl<R: Clone, B> Clone for HashGateAuthLayer<R, B> {
+    fn clone(&self) -> Self {
+        HashGateAuthLayer {
+            resolver: self.resolver.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+impl<R, B: Default + 'static> HashGateAuthLayer<R, B> {
+    /// Create a layer resolving tokens with a `TokenResolver`, either a
+    /// `HashGateClient` (remote) or a `CachedTokenResolver` (cached).
+    ///
+    /// Tokens are read from the `Authorization` header (or gRPC metadata), and
+    /// rejections are rendered as plain HTTP responses by default.
+    pub fn new(resolver: R) -> HashGateAuthLayer<R, B> {
+        HashGateAuthLayer {
+            resolver,
+            config: AuthConfig {
+                token_header: header::AUTHORIZATION,
+                public_paths: Vec::new(),
+                public_prefixes: Vec::new(),
+                render: Arc::new(http_rejection),
+            },
+        }
+    }
+}
+impl<R, B> HashGateAuthLayer<R, B> {
+    /// Read tokens from a different header (or gRPC metadata key).
+    pub fn token_header(mut self, header: HeaderName) -> Self {
+        self.config.token_header = header;
+        self
+    }
+
+    /// Skip authentication for an exact path.
+    pub fn public_path(mut self, path: &str) -> Self {
+        let path = normalize_path(path).unwrap_or_else(|| path.to_owned());
+        self.config.public_paths.push(path);
+        self
+    }
+
+    /// Skip authentication for a path and every path under it
+    /// (ex: `/public` matches `/public/logo.png`, but not `/publicity`).
+    pub fn public_prefix(mut self, prefix: &str) -> Self {
+        let prefix = normalize_path(prefix).unwrap_or_else(|| prefix.to_owned());
+        self.config.public_prefixes.push(prefix);
+        self
+    }
+
+    /// Render rejections with a custom function (ex: `grpc_rejection`).
+    pub fn render_rejection<F>(mut self, render: F) -> Self
+    where
+        F: Fn(Rejection) -> Response<B> + Send + Sync + 'static,
+    {
+        self.config.render = Arc::new(render);
+        self
+    }
+}
+impl<S, R: Clone, B> Layer<S> for HashGateAuthLayer<R, B> {
+    type Service = HashGateAuth<S, R, B>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HashGateAuth {
+            inner,
+            resolver: self.resolver.clone(),
+            config: Arc::new(self.config.clone()),
+        }
+    }
+}
+
+/// The service created by `HashGateAuthLayer`.
+pub struct HashGateAuth<S, R, B> {
+    inner: S,
+    resolver: R,
+    config: Arc<AuthConfig<B>>,
+}
+impl<S: Clone, R: Clone, B> Clone for HashGateAuth<S, R, B> {
+    fn clone(&self) -> Self {
+        HashGateAuth {
+            inner: self.inner.clone(),
+            resolver: self.resolver.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+impl<S, R, ReqBody, B> Service<Request<ReqBody>> for HashGateAuth<S, R, B>
+where
+    S: Service<Request<ReqBody>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    R: TokenResolver + Clone + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+    B: 'static,
+{
+    type Response = Response<B>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // Take the service that was driven to readiness, leaving a clone in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let resolver = self.resolver.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            if config.is_public(req.uri().path()) {
+                return inner.call(req).await;
+            }
+
+            let token = req
+                .headers()
+                .get(&config.token_header)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| config.extract_token(value))
+                .map(str::to_owned);
+
+            let Some(token) = token else {
+                return Ok((config.render)(Rejection::MissingToken));
+            };
+
+            match resolver.resolve(&token).await {
+                Ok(user) => {
+                    req.extensions_mut().insert(user);
+                    inner.call(req).await
+                }
+                Err(e) if e.is_unavailable() => Ok((config.render)(Rejection::Unavailable)),
+                Err(_) => Ok((config.render)(Rejection::InvalidToken)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::HashGateError, integrations::tests::user, operations::user::User};
+    use futures_util::FutureExt;
+    use std::convert::Infallible;
+
+    #[derive(Clone)]
+    /// Resolves `valid`, fails like an unavailable HashGate for `down`, and rejects the rest.
+    struct StubResolver;
+    impl TokenResolver for StubResolver {
+        async fn resolve(&self, token: &str) -> Result<User, HashGateError> {
+            match token {
+                "valid" => Ok(user()),
+                "down" => Err(HashGateError::ServerUnavailable { request_id: None }),
+                _ => Err(HashGateError::UserNotFound { request_id: None }),
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    /// Responds with the username of the `User` in the request extensions.
+    struct Echo;
+    impl Service<Request<()>> for Echo {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Response<String>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            let username = req
+                .extensions()
+                .get::<User>()
+                .map(|user| user.username.clone())
+                .unwrap_or_default();
+            std::future::ready(Ok(Response::new(username)))
+        }
+    }
+
+    fn call(path: &str, authorization: Option<&str>) -> Response<String> {
+        let mut service = HashGateAuthLayer::new(StubResolver)
+            .public_prefix("/public")
+            .layer(Echo);
+
+        let mut req = Request::builder().uri(path);
+        if let Some(authorization) = authorization {
+            req = req.header(header::AUTHORIZATION, authorization);
+        }
+
+        service
+            .call(req.body(()).unwrap())
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_tokens_reach_the_inner_service() {
+        let resp = call("/private", Some("Bearer valid"));
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.body(), "alice");
+    }
+
+    #[test]
+    fn missing_and_invalid_tokens_are_unauthorized() {
+        for authorization in [
+            None,
+            Some("Bearer"),
+            Some("Basic valid"),
+            Some("Bearer wrong"),
+        ] {
+            let resp = call("/private", authorization);
+
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(resp.headers()[header::WWW_AUTHENTICATE], "Bearer");
+            assert!(resp.body().is_empty());
+        }
+    }
+
+    #[test]
+    fn unavailable_hashgate_is_service_unavailable() {
+        let resp = call("/private", Some("Bearer down"));
+
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(resp.body().is_empty());
+    }
+
+    #[test]
+    fn public_paths_skip_authentication() {
+        let resp = call("/public/logo.png", None);
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.body().is_empty());
+    }
+
+    fn config() -> AuthConfig<()> {
+        HashGateAuthLayer::<(), ()>::new(())
+            .public_path("/health")
+            .public_prefix("/public/")
+            .config
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path("/a//b/./c/").as_deref(), Some("/a/b/c"));
+        assert_eq!(normalize_path("/a/../../b").as_deref(), Some("/b"));
+        assert_eq!(normalize_path("/a/%2e%2E/b").as_deref(), Some("/b"));
+        assert_eq!(normalize_path("/a%2fb"), None);
+        assert_eq!(normalize_path("/a/%zz"), None);
+    }
+
+    #[test]
+    fn prefixes_match_whole_segments() {
+        let config = config();
+
+        assert!(config.is_public("/public"));
+        assert!(config.is_public("/public/logo.png"));
+        assert!(config.is_public("/health"));
+        assert!(!config.is_public("/publicity"));
+        assert!(!config.is_public("/health/admin"));
+    }
+
+    #[test]
+    fn traversal_out_of_a_prefix_is_not_public() {
+        let config = config();
+
+        assert!(!config.is_public("/public/../admin"));
+        assert!(!config.is_public("/public/%2e%2e/admin"));
+        assert!(!config.is_public("/public%2f..%2fadmin"));
+        assert!(config.is_public("/admin/../public/logo.png"));
+    }
+}
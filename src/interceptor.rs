@@ -0,0 +1,60 @@
+use crate::error::HashGateError;
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Request, Response,
+};
+
+#[derive(Debug, Clone)]
+/// What a request made through `HashGateClient::post()` or `HashGateClient::get()` is for.
+pub struct RequestContext {
+    /// The HTTP method of the request (ex: `POST`).
+    pub method: &'static str,
+
+    /// The endpoint of the request (ex: `user/sign-in`).
+    pub endpoint: String,
+
+    /// The id sent as the `X-Request-Id` header.
+    pub request_id: String,
+
+    /// The key sent as the `Idempotency-Key` header, if any.
+    pub idempotency_key: Option<String>,
+}
+
+/// Hooks around every request the client sends to HashGate.
+///
+/// Interceptors run in the order they were added, and every hook has a
+/// default that does nothing, so only the needed hooks have to be implemented.
+///
+/// NOTE: Requests sent with `HashGateClient::send_request()` are built by the caller,
+/// so only `on_response` and `on_error` run for them.
+pub trait Interceptor: Send + Sync + std::fmt::Debug {
+    /// Inspect or modify an outgoing request (ex: add headers),
+    /// called again if the request is retried after re-authing the client.
+    fn on_request(&self, _context: &RequestContext, _request: &mut Request) {}
+
+    /// Observe the response to a request.
+    fn on_response(&self, _context: &RequestContext, _response: &Response) {}
+
+    /// Observe a request that failed without a response.
+    fn on_error(&self, _context: &RequestContext, _error: &HashGateError) {}
+}
+
+#[derive(Debug, Clone)]
+/// An `Interceptor` setting a header on every request (ex: a tenant id).
+pub struct SetHeader {
+    name: HeaderName,
+    value: HeaderValue,
+}
+impl SetHeader {
+    /// Create a `SetHeader` interceptor.
+    pub fn new(name: HeaderName, value: HeaderValue) -> SetHeader {
+        SetHeader { name, value }
+    }
+}
+impl Interceptor for SetHeader {
+    fn on_request(&self, _context: &RequestContext, request: &mut Request) {
+        request
+            .headers_mut()
+            .insert(self.name.clone(), self.value.clone());
+    }
+}
@@ -1,5 +1,15 @@
+pub mod circuit_breaker;
 pub mod client;
 pub mod config;
 pub mod error;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod integrations;
+pub mod interceptor;
 pub mod operations;
+pub mod provider;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
 pub mod types;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
@@ -0,0 +1,89 @@
+use crate::{
+    error::HashGateError,
+    operations::delivery::{CodeDelivery, CodeMessage, CodePurpose, DeliveryChannel},
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::path::PathBuf;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A single delivered code, as written to the file.
+struct FileDeliveryRecord<'a> {
+    channel: DeliveryChannel,
+    recipient: &'a str,
+    code: &'a str,
+    purpose: CodePurpose,
+    expires_at: Option<NaiveDateTime>,
+    subject: &'a str,
+    body: &'a str,
+}
+
+#[derive(Debug, Clone)]
+/// A `CodeDelivery` that appends every code to a JSONL file.
+///
+/// NOTE: This is meant for tests and local development,
+/// the raw codes are written to the file in plain text.
+pub struct FileDelivery {
+    path: PathBuf,
+}
+impl FileDelivery {
+    /// Create a `FileDelivery` writing to the given path.
+    pub fn new(path: impl Into<PathBuf>) -> FileDelivery {
+        FileDelivery { path: path.into() }
+    }
+}
+impl CodeDelivery for FileDelivery {
+    async fn deliver(&self, message: &CodeMessage<'_>) -> Result<(), HashGateError> {
+        let record = FileDeliveryRecord {
+            channel: message.channel,
+            recipient: message.recipient,
+            code: message.code,
+            purpose: message.purpose,
+            expires_at: message.expires_at,
+            subject: &message.subject,
+            body: &message.body,
+        };
+        let mut line = serde_json::to_string(&record).map_err(|e| message.delivery_error(e))?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| message.delivery_error(e))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| message.delivery_error(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delivery_errors_carry_the_request_id() {
+        let delivery = FileDelivery::new(std::env::temp_dir().join("hashgate-missing/codes.jsonl"));
+        let message = CodeMessage {
+            user: None,
+            channel: DeliveryChannel::Email,
+            recipient: "alice@example.com",
+            code: "123456",
+            purpose: CodePurpose::Verification,
+            expires_at: None,
+            subject: String::new(),
+            body: String::new(),
+            request_id: Some("req-1"),
+        };
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let error = runtime.block_on(delivery.deliver(&message)).unwrap_err();
+        assert!(matches!(error, HashGateError::Delivery { .. }));
+        assert_eq!(error.request_id(), Some("req-1"));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    client::HashGateClient, error::HashGateError, operations::user::User, types::identity::Email,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use uuid::Uuid;
+
+pub mod file;
+#[cfg(feature = "smtp")]
+pub mod smtp;
+
+#[derive(Deserialize, Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+/// The channels HashGate can deliver codes through.
+pub enum DeliveryChannel {
+    /// Deliver the code to the user's email.
+    Email,
+
+    /// Deliver the code to the user's phone through sms.
+    Sms,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq)]
+/// How a verification or password reset code gets to the user.
+pub enum DeliveryMethod {
+    /// HashGate delivers the code itself, only the session id is returned.
+    HashGate {
+        /// The channel to deliver the code through.
+        channel: DeliveryChannel,
+
+        /// The id of the template to render the code with,
+        /// the pool's default template is used if not set.
+        template_id: Option<String>,
+    },
+
+    /// The code is returned to the caller, who is responsible for delivering it.
+    ///
+    /// NOTE: This puts the raw code in your backend (and possibly your logs),
+    /// so it has to be explicitly opted in to.
+    ReturnCode,
+}
+impl Default for DeliveryMethod {
+    fn default() -> Self {
+        DeliveryMethod::HashGate {
+            channel: DeliveryChannel::Email,
+            template_id: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+/// What a delivered code is for.
+pub enum CodePurpose {
+    /// Verifying a user.
+    Verification,
+
+    /// Resetting a user's password.
+    PasswordReset,
+}
+impl std::fmt::Display for CodePurpose {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Verification => write!(f, "verification"),
+            Self::PasswordReset => write!(f, "password reset"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+/// A subject and body template for delivering codes.
+///
+/// The placeholders `{{code}}`, `{{username}}`, `{{purpose}}` and
+/// `{{expires_at}}` get replaced when the template is rendered.
+pub struct CodeTemplate {
+    /// The subject template.
+    pub subject: String,
+
+    /// The body template.
+    pub body: String,
+}
+impl CodeTemplate {
+    /// Get the default template for a `CodePurpose`.
+    pub fn for_purpose(purpose: CodePurpose) -> CodeTemplate {
+        match purpose {
+            CodePurpose::Verification => CodeTemplate {
+                subject: String::from("Your verification code"),
+                body: String::from("Hi {{username}}, your verification code is {{code}}."),
+            },
+            CodePurpose::PasswordReset => CodeTemplate {
+                subject: String::from("Your password reset code"),
+                body: String::from(
+                    "Your password reset code is {{code}}, it expires at {{expires_at}}.",
+                ),
+            },
+        }
+    }
+
+    /// Render the template into a (subject, body) pair.
+    pub fn render(
+        &self,
+        code: &str,
+        username: Option<&str>,
+        purpose: CodePurpose,
+        expires_at: Option<NaiveDateTime>,
+    ) -> (String, String) {
+        let expires_at = expires_at
+            .map(|expires_at| format!("{expires_at} UTC"))
+            .unwrap_or_default();
+        let purpose = purpose.to_string();
+
+        let render = |template: &str| {
+            template
+                .replace("{{code}}", code)
+                .replace("{{username}}", username.unwrap_or_default())
+                .replace("{{purpose}}", &purpose)
+                .replace("{{expires_at}}", &expires_at)
+        };
+
+        (render(&self.subject), render(&self.body))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A rendered code ready to be delivered.
+pub struct CodeMessage<'a> {
+    /// The user the code is for, if known.
+    pub user: Option<&'a User>,
+
+    /// The channel to deliver the code through.
+    pub channel: DeliveryChannel,
+
+    /// Where to deliver the code to.
+    pub recipient: &'a str,
+
+    /// The raw code.
+    pub code: &'a str,
+
+    /// What the code is for.
+    pub purpose: CodePurpose,
+
+    /// When the code expires, if HashGate reported it.
+    pub expires_at: Option<NaiveDateTime>,
+
+    /// The rendered subject.
+    pub subject: String,
+
+    /// The rendered body.
+    pub body: String,
+
+    /// The `X-Request-Id` of the HashGate request that issued the code.
+    pub request_id: Option<&'a str>,
+}
+impl CodeMessage<'_> {
+    /// Build a `HashGateError::Delivery` for the message, carrying its request id.
+    pub fn delivery_error(&self, reason: impl ToString) -> HashGateError {
+        HashGateError::Delivery {
+            reason: reason.to_string(),
+            request_id: self.request_id.map(str::to_owned),
+        }
+    }
+}
+
+/// Deliver codes to users yourself (ex: email, sms, or a test sink).
+pub trait CodeDelivery {
+    /// Deliver a rendered code to its recipient.
+    fn deliver(
+        &self,
+        message: &CodeMessage<'_>,
+    ) -> impl Future<Output = Result<(), HashGateError>> + Send;
+}
+
+impl User {
+    /// Initialize a verification session for the `User` and deliver the code
+    /// to their email or phone number through your own `CodeDelivery`.
+    ///
+    /// NOTE: Emails are validated and normalized (see `Email`) before anything is sent.
+    ///
+    /// Returns the verification session id, to then verify the code the user gives to you.
+    pub async fn send_verification<D: CodeDelivery>(
+        &mut self,
+        client: &mut HashGateClient,
+        channel: DeliveryChannel,
+        delivery: &D,
+        template: &CodeTemplate,
+    ) -> Result<Uuid, HashGateError> {
+        let recipient = match channel {
+            DeliveryChannel::Email => {
+                let email = self.email.as_deref().ok_or(HashGateError::MissingEmail)?;
+                String::from(Email::parse(email)?)
+            }
+            DeliveryChannel::Sms => self
+                .phone_number
+                .clone()
+                .ok_or(HashGateError::MissingPhoneNumber)?,
+        };
+
+        let (resp, request_id) = client
+            .try_init_user_verification(&self.id, &DeliveryMethod::ReturnCode)
+            .await?;
+        let code = resp
+            .verification_code
+            .ok_or_else(|| HashGateError::ServerError {
+                request_id: request_id.clone(),
+            })?;
+
+        let purpose = CodePurpose::Verification;
+        let (subject, body) = template.render(&code, Some(&self.username), purpose, None);
+        let message = CodeMessage {
+            user: Some(self),
+            channel,
+            recipient: &recipient,
+            code: &code,
+            purpose,
+            expires_at: None,
+            subject,
+            body,
+            request_id: request_id.as_deref(),
+        };
+        delivery.deliver(&message).await?;
+
+        Ok(resp.verification_session_id)
+    }
+}
+
+impl HashGateClient {
+    /// Initialize a password reset for a user and deliver the code
+    /// to their email through your own `CodeDelivery`.
+    ///
+    /// NOTE: The email is validated and normalized (see `Email`) before anything is sent.
+    ///
+    /// Returns the verification session id, to then verify
+    /// the reset with `HashGateClient::verify_password_reset()`.
+    pub async fn send_password_reset<D: CodeDelivery>(
+        &mut self,
+        email: &str,
+        delivery: &D,
+        template: &CodeTemplate,
+    ) -> Result<Uuid, HashGateError> {
+        let email = Email::parse(email)?;
+        let (resp, request_id) = self
+            .try_init_password_reset(email.as_str(), &DeliveryMethod::ReturnCode)
+            .await?;
+        let code = resp
+            .verification_code
+            .ok_or_else(|| HashGateError::ServerError {
+                request_id: request_id.clone(),
+            })?;
+
+        let purpose = CodePurpose::PasswordReset;
+        let (subject, body) = template.render(&code, None, purpose, resp.expires_at);
+        let message = CodeMessage {
+            user: None,
+            channel: DeliveryChannel::Email,
+            recipient: email.as_str(),
+            code: &code,
+            purpose,
+            expires_at: resp.expires_at,
+            subject,
+            body,
+            request_id: request_id.as_deref(),
+        };
+        delivery.deliver(&message).await?;
+
+        Ok(resp.verification_session_id)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{
+    error::HashGateError,
+    operations::delivery::{CodeDelivery, CodeMessage, DeliveryChannel},
+};
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+#[derive(Clone)]
+/// A `CodeDelivery` that sends codes as emails over SMTP.
+pub struct SmtpDelivery {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+impl SmtpDelivery {
+    /// Try to create a `SmtpDelivery` for a relay (TLS) SMTP server.
+    pub fn try_new(
+        host: &str,
+        username: &str,
+        password: &str,
+        from: &str,
+    ) -> Result<SmtpDelivery, HashGateError> {
+        let from = from
+            .parse::<Mailbox>()
+            .map_err(|e| HashGateError::Delivery {
+                reason: e.to_string(),
+                request_id: None,
+            })?;
+
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+            .map_err(|e| HashGateError::Delivery {
+                reason: e.to_string(),
+                request_id: None,
+            })?
+            .credentials(Credentials::new(username.to_owned(), password.to_owned()))
+            .build();
+
+        Ok(SmtpDelivery { transport, from })
+    }
+}
+impl CodeDelivery for SmtpDelivery {
+    async fn deliver(&self, message: &CodeMessage<'_>) -> Result<(), HashGateError> {
+        if message.channel != DeliveryChannel::Email {
+            return Err(message.delivery_error("SMTP Can Only Deliver To Email"));
+        }
+
+        let to = message
+            .recipient
+            .parse::<Mailbox>()
+            .map_err(|e| message.delivery_error(e))?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&message.subject)
+            .body(message.body.clone())
+            .map_err(|e| message.delivery_error(e))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| message.delivery_error(e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::user::User,
+    types::{requests::GetEventsReq, responses::GetEventsResp},
+};
+use chrono::NaiveDateTime;
+use futures_util::{stream, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+/// The different types of user events HashGate records.
+pub enum UserEventType {
+    /// The user signed in.
+    SignInSucceeded,
+
+    /// A sign in attempt for the user failed.
+    SignInFailed,
+
+    /// The user changed their password.
+    PasswordChanged,
+
+    /// The user reset their password.
+    PasswordReset,
+
+    /// A verification session was started for the user.
+    VerificationStarted,
+
+    /// The user was verified.
+    Verified,
+
+    /// A custom attribute of the user changed.
+    AttributeChanged,
+
+    /// An admin acted on the user (ex: unlock, group change).
+    AdminAction,
+
+    /// An event type this version of the SDK doesn't know about yet.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// An event in the history of a user.
+pub struct UserEvent {
+    /// The uuid of the event.
+    pub event_id: Uuid,
+
+    /// The uuid of the user the event is about, if any.
+    pub user_id: Option<Uuid>,
+
+    /// The type of the event.
+    pub event_type: UserEventType,
+
+    /// When the event happened.
+    pub occurred_at: NaiveDateTime,
+
+    /// The IP address the event came from, if known.
+    pub ip_address: Option<String>,
+
+    /// Extra details about the event (ex: the attribute key that changed).
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+/// A filter for user events, every field is optional.
+pub struct EventFilter {
+    /// Only events about this user.
+    pub user_id: Option<Uuid>,
+
+    /// Only events of these types, all types if empty.
+    pub event_types: Vec<UserEventType>,
+
+    /// Only events that happened at or after this date.
+    pub since: Option<NaiveDateTime>,
+
+    /// Only events that happened before this date.
+    pub until: Option<NaiveDateTime>,
+
+    /// The number of events per page.
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+/// A single page of user events.
+pub struct EventsPage {
+    /// The events in the page, newest first.
+    pub events: Vec<UserEvent>,
+
+    /// The cursor for the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+impl HashGateClient {
+    /// Get a single page of events in your user pool.
+    pub async fn pool_events_page(
+        &mut self,
+        filter: &EventFilter,
+        cursor: Option<String>,
+    ) -> Result<EventsPage, HashGateError> {
+        let endpoint = "pool/events";
+
+        let payload = GetEventsReq {
+            filter: filter.clone(),
+            cursor,
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetEventsResp>().await?;
+            Ok(EventsPage {
+                events: resp_body.events,
+                next_cursor: resp_body.next_cursor,
+            })
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Stream every event in your user pool matching a filter,
+    /// fetching pages from HashGate as the stream is polled.
+    pub fn pool_events(
+        &self,
+        filter: EventFilter,
+    ) -> impl Stream<Item = Result<UserEvent, HashGateError>> {
+        let client = self.clone();
+
+        stream::try_unfold(
+            (client, filter, None, false),
+            |(mut client, filter, cursor, is_done)| async move {
+                if is_done {
+                    return Ok::<_, HashGateError>(None);
+                }
+
+                let page = client.pool_events_page(&filter, cursor).await?;
+                let is_done = page.next_cursor.is_none();
+
+                Ok(Some((
+                    page.events,
+                    (client, filter, page.next_cursor, is_done),
+                )))
+            },
+        )
+        .map_ok(|events| stream::iter(events.into_iter().map(Ok)))
+        .try_flatten()
+    }
+}
+
+impl User {
+    /// Stream every event in the `User`s history, newest first.
+    pub fn events(
+        &self,
+        client: &HashGateClient,
+    ) -> impl Stream<Item = Result<UserEvent, HashGateError>> {
+        client.pool_events(EventFilter {
+            user_id: Some(self.id),
+            ..EventFilter::default()
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::user::User,
+    types::{
+        requests::{CreateGroupReq, GetUserGroupsReq, GroupMembershipReq},
+        responses::{CreateGroupResp, GetGroupsResp, GroupMembershipResp},
+    },
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The id of the admin group every user pool is created with.
+pub const ADMIN_GROUP_ID: i32 = 1;
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A group of users within your user pool.
+pub struct Group {
+    /// The id of the group.
+    pub group_id: i32,
+
+    /// The name of the group.
+    pub group_name: String,
+
+    /// The permissions granted to members of the group (ex: `billing:write`).
+    pub permissions: Vec<String>,
+
+    /// The creation date of the group.
+    pub creation_date: NaiveDateTime,
+}
+
+/// Check if a granted permission covers a required one.
+///
+/// Grants can end in a wildcard segment, so `billing:*` covers `billing:write`
+/// and `*` covers everything. A `*` that isn't a whole segment (ex: `admin*`)
+/// is not a wildcard.
+pub fn permission_matches(granted: &str, required: &str) -> bool {
+    if granted == "*" {
+        return true;
+    }
+
+    match granted.strip_suffix(":*") {
+        Some(prefix) => required
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .is_some_and(|rest| !rest.is_empty()),
+        None => granted == required,
+    }
+}
+
+impl User {
+    /// Check if the `User` belongs to a group.
+    pub fn in_group(&self, group_id: i32) -> bool {
+        self.group_id == Some(group_id) || self.group_ids.contains(&group_id)
+    }
+
+    /// Check if the `User` belongs to the admin group.
+    pub fn is_admin(&self) -> bool {
+        self.in_group(ADMIN_GROUP_ID)
+    }
+
+    /// Check if the `User` has been granted a permission through their groups.
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions
+            .iter()
+            .any(|granted| permission_matches(granted, permission))
+    }
+
+    /// Get all the groups the `User` belongs to.
+    pub async fn groups(&self, client: &mut HashGateClient) -> Result<Vec<Group>, HashGateError> {
+        let endpoint = "user/get-groups";
+
+        let payload = GetUserGroupsReq { user_id: self.id };
+
+        let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetGroupsResp>().await?;
+            Ok(resp_body.groups)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+impl HashGateClient {
+    /// Create a group in your user pool.
+    pub async fn create_group(
+        &mut self,
+        group_name: &str,
+        permissions: Vec<String>,
+    ) -> Result<Group, HashGateError> {
+        self.try_create_group(group_name, permissions, None).await
+    }
+
+    /// Create a group the same as `HashGateClient::create_group()`, with an
+    /// idempotency key so retrying with the same key only creates it once.
+    pub async fn create_group_idempotent(
+        &mut self,
+        group_name: &str,
+        permissions: Vec<String>,
+        idempotency_key: &str,
+    ) -> Result<Group, HashGateError> {
+        self.try_create_group(group_name, permissions, Some(idempotency_key))
+            .await
+    }
+
+    async fn try_create_group(
+        &mut self,
+        group_name: &str,
+        permissions: Vec<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Group, HashGateError> {
+        let endpoint = "group/create";
+
+        let payload = CreateGroupReq {
+            group_name: group_name.to_owned(),
+            permissions,
+        };
+
+        let resp = self
+            .post_idempotent(endpoint, &payload, idempotency_key)
+            .await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<CreateGroupResp>().await?;
+            resp_body
+                .group
+                .ok_or(HashGateError::ServerError { request_id })
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// List all the groups in your user pool.
+    pub async fn list_groups(&mut self) -> Result<Vec<Group>, HashGateError> {
+        let endpoint = "group/list";
+
+        let resp = self.get(endpoint).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetGroupsResp>().await?;
+            Ok(resp_body.groups)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Add a user to a group.
+    pub async fn add_user_to_group(
+        &mut self,
+        user_id: &Uuid,
+        group_id: i32,
+    ) -> Result<GroupMembershipResp, HashGateError> {
+        self.update_group_membership("group/add-user", user_id, group_id)
+            .await
+    }
+
+    /// Remove a user from a group.
+    pub async fn remove_user_from_group(
+        &mut self,
+        user_id: &Uuid,
+        group_id: i32,
+    ) -> Result<GroupMembershipResp, HashGateError> {
+        self.update_group_membership("group/remove-user", user_id, group_id)
+            .await
+    }
+
+    async fn update_group_membership(
+        &mut self,
+        endpoint: &str,
+        user_id: &Uuid,
+        group_id: i32,
+    ) -> Result<GroupMembershipResp, HashGateError> {
+        let payload = GroupMembershipReq {
+            user_id: user_id.to_owned(),
+            group_id,
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GroupMembershipResp>().await?;
+            Ok(resp_body)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::permission_matches;
+
+    #[test]
+    fn exact_grants_match_only_themselves() {
+        assert!(permission_matches("billing:write", "billing:write"));
+        assert!(!permission_matches("billing:write", "billing:read"));
+        assert!(!permission_matches("billing", "billing:write"));
+    }
+
+    #[test]
+    fn wildcards_cover_whole_segments() {
+        assert!(permission_matches("*", "billing:write"));
+        assert!(permission_matches("billing:*", "billing:write"));
+        assert!(permission_matches("billing:*", "billing:invoice:read"));
+        assert!(!permission_matches("billing:*", "billing"));
+        assert!(!permission_matches("billing:*", "billing:"));
+        assert!(!permission_matches("billing:*", "billingx:write"));
+    }
+
+    #[test]
+    fn partial_segment_wildcards_are_literal() {
+        assert!(!permission_matches("admin*", "administrator:delete"));
+        assert!(!permission_matches("admin*", "admin:delete"));
+        assert!(!permission_matches("billing:wr*", "billing:write"));
+        assert!(permission_matches("admin*", "admin*"));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::user::User,
+    types::{identity::Username, requests::UnlockUserReq, responses::UnlockUserResp},
+};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+/// A local per username and per IP limiter for failed sign in attempts,
+/// short circuiting sign ins before they reach HashGate.
+///
+/// A sign in only counts as successful once it's complete, so a wrong MFA code
+/// counts as a failure of the sign in that started the challenge.
+///
+/// NOTE: The limiter lives in memory, so it's per process. Clones share state.
+pub struct LoginThrottle {
+    max_failures_per_username: usize,
+    max_failures_per_ip: usize,
+    window: Duration,
+    failures: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
+    challenges: Arc<Mutex<HashMap<Uuid, PendingChallenge>>>,
+}
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        LoginThrottle::new(5, 20, Duration::from_secs(15 * 60))
+    }
+}
+impl LoginThrottle {
+    /// Create a `LoginThrottle` allowing a number of failed sign ins
+    /// per username and per IP within a sliding window.
+    pub fn new(
+        max_failures_per_username: usize,
+        max_failures_per_ip: usize,
+        window: Duration,
+    ) -> LoginThrottle {
+        LoginThrottle {
+            max_failures_per_username,
+            max_failures_per_ip,
+            window,
+            failures: Arc::new(Mutex::new(HashMap::new())),
+            challenges: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the throttle keys (with their limit) for a sign in attempt.
+    fn keys(&self, username: &str, ip: Option<IpAddr>) -> Vec<(String, usize)> {
+        let mut keys = vec![(
+            format!("username:{}", normalize_username(username)),
+            self.max_failures_per_username,
+        )];
+        if let Some(ip) = ip {
+            keys.push((format!("ip:{ip}"), self.max_failures_per_ip));
+        }
+
+        keys
+    }
+
+    /// Lock the failures, recovering them if a thread panicked while holding the lock
+    /// (they're only timestamps, so they're still valid).
+    fn failures(&self) -> MutexGuard<'_, HashMap<String, Vec<Instant>>> {
+        self.failures.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the pending challenges, recovering them the same as the failures.
+    fn challenges(&self) -> MutexGuard<'_, HashMap<Uuid, PendingChallenge>> {
+        self.challenges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Remember the sign in an MFA challenge was started by,
+    /// so completing it is throttled the same as the sign in.
+    pub fn start_challenge(&self, challenge_id: Uuid, username: &str, ip: Option<IpAddr>) {
+        let mut challenges = self.challenges();
+
+        // Challenges expire well within the window, so older ones are abandoned
+        challenges.retain(|_, challenge| challenge.started_at.elapsed() < self.window);
+        challenges.insert(
+            challenge_id,
+            PendingChallenge {
+                username: username.to_owned(),
+                ip,
+                started_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Check if an attempt at an MFA challenge is allowed, the same as `LoginThrottle::check()`
+    /// for the sign in that started it.
+    ///
+    /// NOTE: Challenges started outside of this throttle (ex: by another process)
+    /// aren't known, so they aren't throttled and `None` is returned.
+    pub fn check_challenge(
+        &self,
+        challenge_id: &Uuid,
+    ) -> Result<Option<ThrottleAttempt>, HashGateError> {
+        let Some(challenge) = self.challenges().get(challenge_id).cloned() else {
+            return Ok(None);
+        };
+
+        let mut attempt = self.check(&challenge.username, challenge.ip)?;
+        attempt.challenge_id = Some(*challenge_id);
+        Ok(Some(attempt))
+    }
+
+    /// Check if a sign in attempt is allowed, returning
+    /// `HashGateError::RateLimited` if it should be short circuited.
+    ///
+    /// NOTE: An allowed attempt is reserved as a failure until it's settled with
+    /// `ThrottleAttempt::succeeded()` or `ThrottleAttempt::failed()`, so concurrent
+    /// attempts can't get past the limit. Dropping it releases the reservation.
+    pub fn check(
+        &self,
+        username: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<ThrottleAttempt, HashGateError> {
+        let mut failures = self.failures();
+
+        // Drop stale keys so the map doesn't grow with every username seen
+        failures.retain(|_, attempts| {
+            attempts.retain(|attempt| attempt.elapsed() < self.window);
+            !attempts.is_empty()
+        });
+
+        let keys = self.keys(username, ip);
+        for (key, max_failures) in &keys {
+            let Some(attempts) = failures.get(key) else {
+                continue;
+            };
+
+            if attempts.len() >= *max_failures {
+                let retry_after = attempts
+                    .first()
+                    .map(|oldest| self.window.saturating_sub(oldest.elapsed()))
+                    .unwrap_or_default();
+                return Err(HashGateError::RateLimited { retry_after });
+            }
+        }
+
+        let reserved_at = Instant::now();
+        for (key, _) in &keys {
+            failures.entry(key.clone()).or_default().push(reserved_at);
+        }
+
+        Ok(ThrottleAttempt {
+            throttle: self.clone(),
+            keys: keys.into_iter().map(|(key, _)| key).collect(),
+            reserved_at,
+            challenge_id: None,
+            settled: false,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// The sign in an MFA challenge was started by.
+struct PendingChallenge {
+    username: String,
+    ip: Option<IpAddr>,
+    started_at: Instant,
+}
+
+#[derive(Debug)]
+/// A sign in attempt allowed by a `LoginThrottle`, reserved as a failure until it's settled.
+pub struct ThrottleAttempt {
+    throttle: LoginThrottle,
+    keys: Vec<String>,
+    reserved_at: Instant,
+    challenge_id: Option<Uuid>,
+    settled: bool,
+}
+impl ThrottleAttempt {
+    /// Record the attempt as a failed sign in, keeping its reservation.
+    pub fn failed(mut self) {
+        self.settled = true;
+    }
+
+    /// Record the attempt as a successful sign in, clearing the username's failures.
+    ///
+    /// NOTE: Only call this once the sign in is complete, a sign in waiting
+    /// on an MFA challenge is handed to `LoginThrottle::start_challenge()` instead.
+    pub fn succeeded(mut self) {
+        self.release();
+        if let Some(username_key) = self.keys.first() {
+            self.throttle.failures().remove(username_key);
+        }
+        if let Some(challenge_id) = &self.challenge_id {
+            self.throttle.challenges().remove(challenge_id);
+        }
+        self.settled = true;
+    }
+
+    /// Remove the attempt's reservation.
+    fn release(&self) {
+        let mut failures = self.throttle.failures();
+        for key in &self.keys {
+            if let Some(attempts) = failures.get_mut(key) {
+                if let Some(i) = attempts.iter().position(|a| *a == self.reserved_at) {
+                    attempts.remove(i);
+                }
+            }
+        }
+    }
+}
+impl Drop for ThrottleAttempt {
+    fn drop(&mut self) {
+        if !self.settled {
+            self.release();
+        }
+    }
+}
+
+/// Normalize a username the same as `Username`, so every spelling
+/// of a username shares its throttle key.
+fn normalize_username(username: &str) -> String {
+    Username::parse(username)
+        .map(String::from)
+        .unwrap_or_else(|_| username.trim().to_lowercase())
+}
+
+impl User {
+    /// Check if the `User` is currently locked out.
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .is_some_and(|locked_until| locked_until > chrono::Utc::now().naive_utc())
+    }
+
+    /// Unlock the `User`, clearing their failed sign in attempts.
+    ///
+    /// NOTE: This is an admin action.
+    pub async fn unlock(&mut self, client: &mut HashGateClient) -> Result<(), HashGateError> {
+        let endpoint = "user/unlock";
+
+        let payload = UnlockUserReq { user_id: self.id };
+
+        let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            resp.json::<UnlockUserResp>().await?;
+            self.locked_until = None;
+            Ok(())
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle::new(2, 3, Duration::from_secs(60))
+    }
+
+    #[test]
+    fn reserves_attempts_until_settled() {
+        let throttle = throttle();
+
+        let first = throttle.check("alice", None).unwrap();
+        let second = throttle.check("ALICE", None).unwrap();
+        assert!(matches!(
+            throttle.check("alice", None),
+            Err(HashGateError::RateLimited { .. })
+        ));
+
+        drop(first);
+        second.failed();
+        throttle.check("alice", None).unwrap().failed();
+        assert!(throttle.check("alice", None).is_err());
+    }
+
+    #[test]
+    fn success_clears_username_failures() {
+        let throttle = throttle();
+        let ip = Some(IpAddr::from([127, 0, 0, 1]));
+
+        throttle.check("alice", ip).unwrap().failed();
+        throttle.check("alice", ip).unwrap().succeeded();
+        throttle.check("alice", ip).unwrap().failed();
+        throttle.check("alice", ip).unwrap().failed();
+
+        // The IP keeps its 3 failures, the success only cleared the username
+        assert!(throttle.check("bob", ip).is_err());
+    }
+
+    #[test]
+    fn recovers_from_a_poisoned_lock() {
+        let throttle = throttle();
+        throttle.check("alice", None).unwrap().failed();
+        throttle.check("alice", None).unwrap().failed();
+
+        let poisoner = throttle.clone();
+        let _ = std::thread::spawn(move || {
+            let _failures = poisoner.failures.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(throttle.check("alice", None).is_err());
+    }
+
+    #[test]
+    fn mfa_challenges_count_toward_the_sign_in() {
+        let throttle = throttle();
+        let ip = Some(IpAddr::from([127, 0, 0, 1]));
+        let challenge_id = Uuid::new_v4();
+
+        throttle.check("alice", ip).unwrap().failed();
+        // The right password only releases its reservation, it's not a success yet
+        drop(throttle.check("alice", ip).unwrap());
+        throttle.start_challenge(challenge_id, "alice", ip);
+
+        let attempt = throttle.check_challenge(&challenge_id).unwrap().unwrap();
+        attempt.failed();
+        assert!(throttle.check_challenge(&challenge_id).is_err());
+        assert!(throttle.check_challenge(&Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[test]
+    fn completed_mfa_challenges_clear_failures() {
+        let throttle = throttle();
+        let challenge_id = Uuid::new_v4();
+
+        throttle.check("alice", None).unwrap().failed();
+        throttle.start_challenge(challenge_id, "alice", None);
+        throttle
+            .check_challenge(&challenge_id)
+            .unwrap()
+            .unwrap()
+            .succeeded();
+
+        assert!(throttle.check_challenge(&challenge_id).unwrap().is_none());
+        throttle.check("alice", None).unwrap().failed();
+        throttle.check("alice", None).unwrap().failed();
+    }
+}
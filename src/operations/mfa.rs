@@ -0,0 +1,254 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::user::User,
+    types::{
+        requests::{
+            CompleteMfaChallengeReq, ConfirmTotpReq, EnrollTotpReq, GenerateRecoveryCodesReq,
+        },
+        responses::{AuthResponse, ConfirmTotpResp, EnrollTotpResp, GenerateRecoveryCodesResp},
+    },
+};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq)]
+/// The outcome of a sign in attempt.
+pub enum SignInOutcome {
+    /// The user is signed in, no further steps required.
+    Authenticated {
+        /// The auth token for the user.
+        token: String,
+    },
+
+    /// The user has MFA enabled and must complete a challenge
+    /// with `HashGateClient::complete_mfa_challenge()`.
+    MfaRequired {
+        /// The uuid of the MFA challenge.
+        challenge_id: Uuid,
+    },
+}
+impl SignInOutcome {
+    /// Try to build a `SignInOutcome` from a sign in response.
+    pub(crate) fn try_from_auth_response(resp: AuthResponse) -> Result<Self, HashGateError> {
+        match (resp.token, resp.mfa_challenge_id) {
+            (_, Some(challenge_id)) => Ok(SignInOutcome::MfaRequired { challenge_id }),
+            (Some(token), None) => Ok(SignInOutcome::Authenticated { token }),
+            (None, None) => Err(HashGateError::FailedSignIn { request_id: None }),
+        }
+    }
+}
+
+/// Map a failed MFA response to an error, only rejected codes are `HashGateError::InvalidMfaCode`.
+fn mfa_error(resp: &Response) -> HashGateError {
+    let request_id = request_id(resp);
+    match resp.status() {
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            HashGateError::InvalidMfaCode { request_id }
+        }
+        StatusCode::TOO_MANY_REQUESTS => HashGateError::TooManyAttempts { request_id },
+        _ => HashGateError::ServerError { request_id },
+    }
+}
+
+impl User {
+    /// Start enrolling the `User` in TOTP based MFA.
+    ///
+    /// The returned provisioning uri (`otpauth://`) can be rendered as
+    /// a QR code for authenticator apps. The enrollment is not active
+    /// until it's confirmed with `User::confirm_totp()`.
+    pub async fn enroll_totp(
+        &self,
+        client: &mut HashGateClient,
+    ) -> Result<EnrollTotpResp, HashGateError> {
+        let endpoint = "user/enroll-totp";
+
+        let payload = EnrollTotpReq { user_id: self.id };
+
+        let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<EnrollTotpResp>().await?;
+            Ok(resp_body)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Confirm the `User`s TOTP enrollment with a code from their authenticator app.
+    pub async fn confirm_totp(
+        &self,
+        client: &mut HashGateClient,
+        code: &str,
+    ) -> Result<ConfirmTotpResp, HashGateError> {
+        let endpoint = "user/confirm-totp";
+
+        let payload = ConfirmTotpReq {
+            user_id: self.id,
+            code: code.to_owned(),
+        };
+
+        let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<ConfirmTotpResp>().await?;
+            if resp_body.was_successful {
+                Ok(resp_body)
+            } else {
+                Err(HashGateError::InvalidMfaCode { request_id })
+            }
+        } else {
+            Err(mfa_error(&resp))
+        }
+    }
+
+    /// Generate a fresh set of recovery codes for the `User`.
+    ///
+    /// NOTE: This invalidates any previously generated recovery codes,
+    /// and each code can only be used once.
+    pub async fn generate_recovery_codes(
+        &self,
+        client: &mut HashGateClient,
+    ) -> Result<Vec<String>, HashGateError> {
+        let endpoint = "user/generate-recovery-codes";
+
+        let payload = GenerateRecoveryCodesReq { user_id: self.id };
+
+        let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GenerateRecoveryCodesResp>().await?;
+            Ok(resp_body.recovery_codes)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+impl HashGateClient {
+    /// Complete an MFA challenge with a TOTP code, this is the second
+    /// step of signing in a user with MFA enabled.
+    ///
+    /// NOTE: You must have a challenge id to call this, you can obtain
+    /// one from `HashGateClient::authenticate_user()`.
+    ///
+    /// Wrong codes count as failed sign ins when a `LoginThrottle` is set.
+    pub async fn complete_mfa_challenge(
+        &mut self,
+        challenge_id: &Uuid,
+        code: &str,
+    ) -> Result<String, HashGateError> {
+        self.try_complete_mfa_challenge("user/complete-mfa-challenge", challenge_id, code)
+            .await
+    }
+
+    /// Complete an MFA challenge by consuming one of the user's recovery codes.
+    pub async fn complete_mfa_challenge_with_recovery_code(
+        &mut self,
+        challenge_id: &Uuid,
+        recovery_code: &str,
+    ) -> Result<String, HashGateError> {
+        self.try_complete_mfa_challenge("user/redeem-recovery-code", challenge_id, recovery_code)
+            .await
+    }
+
+    async fn try_complete_mfa_challenge(
+        &mut self,
+        endpoint: &str,
+        challenge_id: &Uuid,
+        code: &str,
+    ) -> Result<String, HashGateError> {
+        let attempt = match self.login_throttle() {
+            Some(throttle) => throttle.check_challenge(challenge_id)?,
+            None => None,
+        };
+
+        let payload = CompleteMfaChallengeReq {
+            challenge_id: challenge_id.to_owned(),
+            code: code.to_owned(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+
+        let token = if resp.status().is_success() {
+            let resp_body = resp.json::<AuthResponse>().await?;
+            match (resp_body.token, resp_body.was_successful) {
+                (Some(token), _) => Ok(token),
+                (None, false) => Err(HashGateError::InvalidMfaCode { request_id }),
+                (None, true) => Err(HashGateError::ServerError { request_id }),
+            }
+        } else {
+            Err(mfa_error(&resp))
+        };
+
+        // Any other error drops the attempt, releasing its reservation
+        if let Some(attempt) = attempt {
+            match &token {
+                Ok(_) => attempt.succeeded(),
+                Err(HashGateError::InvalidMfaCode { .. }) => attempt.failed(),
+                Err(_) => {}
+            }
+        }
+
+        token
+    }
+}
+
+#[cfg(all(test, feature = "fixtures"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Exchange, ReplayMode, Replayer};
+    use futures_util::FutureExt;
+
+    fn complete_challenge(status: u16, response: serde_json::Value) -> HashGateError {
+        let exchange = Exchange {
+            method: String::from("POST"),
+            endpoint: String::from("user/complete-mfa-challenge"),
+            request: None,
+            status,
+            response,
+        };
+        let mut client =
+            HashGateClient::replaying(Replayer::new(vec![exchange], ReplayMode::InOrder));
+
+        client
+            .complete_mfa_challenge(&Uuid::new_v4(), "123456")
+            .now_or_never()
+            .unwrap()
+            .unwrap_err()
+    }
+
+    #[test]
+    fn only_rejected_codes_are_invalid() {
+        let rejected = serde_json::json!({ "wasSuccessful": false });
+        for status in [400, 401, 403] {
+            assert!(matches!(
+                complete_challenge(status, rejected.clone()),
+                HashGateError::InvalidMfaCode { .. }
+            ));
+        }
+        assert!(matches!(
+            complete_challenge(200, rejected.clone()),
+            HashGateError::InvalidMfaCode { .. }
+        ));
+
+        assert!(matches!(
+            complete_challenge(429, rejected.clone()),
+            HashGateError::TooManyAttempts { .. }
+        ));
+        assert!(matches!(
+            complete_challenge(503, rejected.clone()),
+            HashGateError::ServerUnavailable { .. }
+        ));
+        assert!(matches!(
+            complete_challenge(404, rejected),
+            HashGateError::ServerError { .. }
+        ));
+        assert!(matches!(
+            complete_challenge(200, serde_json::json!({ "wasSuccessful": true })),
+            HashGateError::ServerError { .. }
+        ));
+    }
+}
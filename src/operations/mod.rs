@@ -1,2 +1,12 @@
+pub mod delivery;
+pub mod events;
+pub mod group;
+pub mod lockout;
+pub mod mfa;
+pub mod password_policy;
+pub mod password_reset;
+pub mod passwordless;
 pub mod pool;
+pub mod register;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,258 @@
+use crate::{
+    error::HashGateError,
+    operations::password_policy::{PasswordPolicy, PasswordViolation},
+};
+use sha1::{Digest, Sha1};
+use std::{
+    fs::File,
+    future::Future,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+/// The future returned by `BreachSource::range`.
+pub type RangeFuture<'a> = Pin<Box<dyn Future<Output = Result<String, HashGateError>> + Send + 'a>>;
+
+/// A source of Have I Been Pwned style k-anonymity range data.
+pub trait BreachSource: Send + Sync + std::fmt::Debug {
+    /// Get the range for a 5 character uppercase SHA-1 hex prefix,
+    /// as `SUFFIX:COUNT` lines (the HIBP range API format).
+    fn range<'a>(&'a self, prefix: &'a str) -> RangeFuture<'a>;
+}
+
+#[derive(Debug, Clone)]
+/// A `BreachSource` reading from a directory of range files named `<PREFIX>.txt`,
+/// the layout written by the HIBP `PwnedPasswordsDownloader`.
+pub struct RangeDirectory {
+    dir: PathBuf,
+}
+impl RangeDirectory {
+    /// Create a `RangeDirectory` reading from the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> RangeDirectory {
+        RangeDirectory { dir: dir.into() }
+    }
+}
+impl BreachSource for RangeDirectory {
+    fn range<'a>(&'a self, prefix: &'a str) -> RangeFuture<'a> {
+        Box::pin(async move {
+            let path = self.dir.join(format!("{prefix}.txt"));
+            match tokio::fs::read_to_string(path).await {
+                Ok(range) => Ok(range),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+                Err(e) => Err(HashGateError::BreachCheck(e.to_string())),
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A `BreachSource` reading from a single HIBP file of `HASH:COUNT` lines
+/// ordered by hash (ex: `pwned-passwords-sha1-ordered-by-hash.txt`).
+///
+/// NOTE: Ranges are found with a binary search over the file on a blocking thread,
+/// so the file must stay ordered by hash.
+pub struct RangeFile {
+    path: PathBuf,
+}
+impl RangeFile {
+    /// Create a `RangeFile` reading from the given path.
+    pub fn new(path: impl Into<PathBuf>) -> RangeFile {
+        RangeFile { path: path.into() }
+    }
+}
+impl BreachSource for RangeFile {
+    fn range<'a>(&'a self, prefix: &'a str) -> RangeFuture<'a> {
+        let path = self.path.clone();
+        let prefix = prefix.to_owned();
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || read_range(&path, &prefix))
+                .await
+                .map_err(|e| HashGateError::BreachCheck(e.to_string()))?
+                .map_err(|e| HashGateError::BreachCheck(e.to_string()))
+        })
+    }
+}
+
+/// Get the uppercase hash prefix of a `HASH:COUNT` line.
+fn line_prefix(line: &[u8]) -> String {
+    String::from_utf8_lossy(line.get(..5).unwrap_or(line)).to_uppercase()
+}
+
+/// Seek to the start of the first line starting at or after a position.
+fn seek_line_start(reader: &mut BufReader<File>, pos: u64) -> io::Result<u64> {
+    if pos == 0 {
+        return reader.seek(SeekFrom::Start(0));
+    }
+
+    // Starting a byte early keeps a line starting exactly at `pos`
+    reader.seek(SeekFrom::Start(pos - 1))?;
+    let skipped = reader.skip_until(b'\n')?;
+    Ok(pos - 1 + skipped as u64)
+}
+
+/// Read the range for a prefix out of a file ordered by hash.
+fn read_range(path: &Path, prefix: &str) -> io::Result<String> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let mut line = Vec::new();
+
+    // Binary search for the first line at or past the prefix, `lo` is always a line start
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let start = seek_line_start(&mut reader, mid)?;
+        if start >= hi {
+            hi = mid;
+            continue;
+        }
+
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if line_prefix(&line).as_str() < prefix {
+            lo = start + read as u64;
+        } else {
+            hi = mid;
+        }
+    }
+
+    reader.seek(SeekFrom::Start(lo))?;
+    let mut range = String::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 || line_prefix(&line) != prefix {
+            break;
+        }
+
+        let line = String::from_utf8_lossy(&line);
+        range.push_str(line.get(5..).unwrap_or_default().trim_end());
+        range.push('\n');
+    }
+
+    Ok(range)
+}
+
+#[derive(Debug, Clone)]
+/// A `BreachSource` calling a local HTTP stand-in for the HIBP range API,
+/// serving ranges at `<base_url>range/<PREFIX>`.
+pub struct HttpRangeSource {
+    base_url: String,
+    req_client: reqwest::Client,
+}
+impl HttpRangeSource {
+    /// Create a `HttpRangeSource` for the given base url (ex: `http://localhost:8080/`).
+    pub fn new(base_url: &str) -> HttpRangeSource {
+        HttpRangeSource {
+            base_url: base_url.to_owned(),
+            req_client: reqwest::Client::new(),
+        }
+    }
+}
+impl BreachSource for HttpRangeSource {
+    fn range<'a>(&'a self, prefix: &'a str) -> RangeFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}range/{prefix}", self.base_url);
+            let resp = self.req_client.get(url).send().await?;
+
+            if resp.status().is_success() {
+                Ok(resp.text().await?)
+            } else if resp.status() == reqwest::StatusCode::NOT_FOUND {
+                Ok(String::new())
+            } else {
+                Err(HashGateError::BreachCheck(format!(
+                    "Range Source Responded With {}",
+                    resp.status()
+                )))
+            }
+        })
+    }
+}
+
+/// Get the number of times a password appears in a breach,
+/// only the first 5 characters of its SHA-1 hash leave this function.
+pub async fn breach_count(source: &dyn BreachSource, password: &str) -> Result<u64, HashGateError> {
+    let hash = Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<String>();
+    let (prefix, suffix) = hash.split_at(5);
+
+    let range = source.range(prefix).await?;
+    let count = range
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(line_suffix, _)| line_suffix.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+
+    Ok(count)
+}
+
+impl PasswordPolicy {
+    /// Validate a password against the policy, including the breached password
+    /// rule (`reject_breached`) which is checked against the given source.
+    pub async fn validate_with_breach_source(
+        &self,
+        password: &str,
+        source: &dyn BreachSource,
+    ) -> Result<(), HashGateError> {
+        let mut violations = self.validate(password).err().unwrap_or_default();
+
+        if self.reject_breached {
+            let count = breach_count(source, password).await?;
+            if count > 0 {
+                violations.push(PasswordViolation::Breached { count });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(HashGateError::WeakPassword(violations))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range_file(name: &str, lines: &[&str]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("hashgate-{name}-{}.txt", std::process::id()));
+        std::fs::write(&path, lines.join("\r\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_ranges_from_an_ordered_file() {
+        let path = range_file(
+            "ordered",
+            &[
+                "00000AAAA:1",
+                "11111BBBB:2",
+                "11111CCCC:3",
+                "22222DDDD:4",
+                "33333EEEE:5",
+            ],
+        );
+
+        assert_eq!(read_range(&path, "00000").unwrap(), "AAAA:1\n");
+        assert_eq!(read_range(&path, "11111").unwrap(), "BBBB:2\nCCCC:3\n");
+        assert_eq!(read_range(&path, "33333").unwrap(), "EEEE:5\n");
+        assert_eq!(read_range(&path, "12345").unwrap(), "");
+        assert_eq!(read_range(&path, "FFFFF").unwrap(), "");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reads_ranges_from_lowercase_hashes() {
+        let path = range_file("lowercase", &["0000aaaaa:1", "abcdeffff:2"]);
+
+        assert_eq!(read_range(&path, "ABCDE").unwrap(), "ffff:2\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
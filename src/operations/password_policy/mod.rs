@@ -0,0 +1,360 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    types::responses::GetPasswordPolicyResp,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "breach")]
+pub mod breach;
+#[cfg(feature = "strength")]
+pub mod strength;
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// The password rules for a user pool.
+pub struct PasswordPolicy {
+    /// The minimum number of characters.
+    pub min_length: usize,
+
+    /// The maximum number of characters, if any.
+    pub max_length: Option<usize>,
+
+    /// Require at least one lowercase letter.
+    pub require_lowercase: bool,
+
+    /// Require at least one uppercase letter.
+    pub require_uppercase: bool,
+
+    /// Require at least one digit.
+    pub require_digit: bool,
+
+    /// Require at least one symbol.
+    pub require_symbol: bool,
+
+    /// Passwords that are never allowed (compared case insensitively).
+    pub banned_passwords: Vec<String>,
+
+    /// The minimum estimated entropy in bits, if any.
+    pub min_entropy_bits: Option<f64>,
+
+    /// Reject passwords found in known breaches.
+    ///
+    /// NOTE: This needs the `breach` feature and a `BreachSource`, without one the rule is
+    /// skipped (see `HashGateClient::set_require_breach_check()` to reject passwords instead).
+    #[serde(default)]
+    pub reject_breached: bool,
+}
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: 8,
+            max_length: Some(128),
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            banned_passwords: Vec::new(),
+            min_entropy_bits: None,
+            reject_breached: false,
+        }
+    }
+}
+impl PasswordPolicy {
+    /// Validate a password against the policy, returning every rule it breaks.
+    pub fn validate(&self, password: &str) -> Result<(), Vec<PasswordViolation>> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            violations.push(PasswordViolation::TooShort {
+                min_length: self.min_length,
+            });
+        }
+        if let Some(max_length) = self.max_length {
+            if length > max_length {
+                violations.push(PasswordViolation::TooLong { max_length });
+            }
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::MissingLowercase);
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::MissingUppercase);
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::MissingDigit);
+        }
+        if self.require_symbol && !password.chars().any(is_symbol) {
+            violations.push(PasswordViolation::MissingSymbol);
+        }
+        if self
+            .banned_passwords
+            .iter()
+            .any(|banned| banned.to_lowercase() == password.to_lowercase())
+        {
+            violations.push(PasswordViolation::Banned);
+        }
+        if let Some(min_entropy_bits) = self.min_entropy_bits {
+            let entropy_bits = estimate_entropy_bits(password);
+            if entropy_bits < min_entropy_bits {
+                violations.push(PasswordViolation::LowEntropy {
+                    entropy_bits,
+                    min_entropy_bits,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq)]
+/// A password policy rule a password breaks.
+pub enum PasswordViolation {
+    /// The password is shorter than the minimum length.
+    TooShort { min_length: usize },
+
+    /// The password is longer than the maximum length.
+    TooLong { max_length: usize },
+
+    /// The password has no lowercase letters.
+    MissingLowercase,
+
+    /// The password has no uppercase letters.
+    MissingUppercase,
+
+    /// The password has no digits.
+    MissingDigit,
+
+    /// The password has no symbols.
+    MissingSymbol,
+
+    /// The password is on the banned list.
+    Banned,
+
+    /// The password's estimated entropy is too low.
+    LowEntropy {
+        entropy_bits: f64,
+        min_entropy_bits: f64,
+    },
+
+    /// The password has been found in known breaches.
+    Breached { count: u64 },
+}
+impl std::fmt::Display for PasswordViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::TooShort { min_length } => {
+                write!(f, "Password Must Be At Least {min_length} Characters")
+            }
+            Self::TooLong { max_length } => {
+                write!(f, "Password Must Be At Most {max_length} Characters")
+            }
+            Self::MissingLowercase => write!(f, "Password Must Contain A Lowercase Letter"),
+            Self::MissingUppercase => write!(f, "Password Must Contain An Uppercase Letter"),
+            Self::MissingDigit => write!(f, "Password Must Contain A Digit"),
+            Self::MissingSymbol => write!(f, "Password Must Contain A Symbol"),
+            Self::Banned => write!(f, "Password Is Not Allowed"),
+            Self::LowEntropy { .. } => write!(f, "Password Is Too Predictable"),
+            Self::Breached { count } => {
+                write!(f, "Password Has Appeared In {count} Known Breaches")
+            }
+        }
+    }
+}
+
+/// Check if a character counts as a symbol.
+fn is_symbol(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+/// Estimate the entropy of a password in bits, based on its
+/// length and the size of the character classes it uses.
+pub fn estimate_entropy_bits(password: &str) -> f64 {
+    let mut pool_size = 0;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool_size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool_size += 10;
+    }
+    if password.chars().any(|c| c.is_ascii() && is_symbol(c)) {
+        pool_size += 33;
+    }
+    if password.chars().any(|c| c == ' ') {
+        pool_size += 1;
+    }
+    if !password.is_ascii() {
+        pool_size += 100;
+    }
+
+    if pool_size == 0 {
+        0.0
+    } else {
+        password.chars().count() as f64 * f64::from(pool_size).log2()
+    }
+}
+
+impl HashGateClient {
+    /// Get the password policy of your user pool.
+    pub async fn get_password_policy(&mut self) -> Result<PasswordPolicy, HashGateError> {
+        let endpoint = "pool/password-policy";
+
+        let resp = self.get(endpoint).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetPasswordPolicyResp>().await?;
+            Ok(resp_body.password_policy)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Fetch the password policy of your user pool and enforce it
+    /// locally before sending any new passwords to HashGate.
+    pub async fn load_password_policy(&mut self) -> Result<(), HashGateError> {
+        let password_policy = self.get_password_policy().await?;
+        self.set_password_policy(Some(password_policy));
+        Ok(())
+    }
+
+    /// Check a new password against the client's password policy, if one is set.
+    pub(crate) async fn check_password(&self, password: &str) -> Result<(), HashGateError> {
+        let Some(password_policy) = self.password_policy() else {
+            return Ok(());
+        };
+
+        #[cfg(feature = "breach")]
+        if let Some(breach_source) = self.breach_source() {
+            return password_policy
+                .validate_with_breach_source(password, breach_source.as_ref())
+                .await;
+        }
+
+        // Policies fetched from HashGate can set the rule without a breach source being
+        // set up locally, so it's only enforced without one when opted into
+        if password_policy.reject_breached && self.require_breach_check() {
+            return Err(HashGateError::BreachCheck(String::from(
+                "No Breach Source Is Set",
+            )));
+        }
+
+        password_policy
+            .validate(password)
+            .map_err(HashGateError::WeakPassword)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_checks_length() {
+        let policy = PasswordPolicy::default();
+
+        assert!(policy.validate("hunter22").is_ok());
+        assert_eq!(
+            policy.validate("hunter2"),
+            Err(vec![PasswordViolation::TooShort { min_length: 8 }])
+        );
+        assert_eq!(
+            policy.validate(&"a".repeat(129)),
+            Err(vec![PasswordViolation::TooLong { max_length: 128 }])
+        );
+    }
+
+    #[test]
+    fn reports_every_violation() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(
+            policy.validate("       "),
+            Err(vec![
+                PasswordViolation::TooShort { min_length: 8 },
+                PasswordViolation::MissingLowercase,
+                PasswordViolation::MissingUppercase,
+                PasswordViolation::MissingDigit,
+                PasswordViolation::MissingSymbol,
+            ])
+        );
+        assert!(policy.validate("Hunter2!").is_ok());
+    }
+
+    #[test]
+    fn banned_passwords_ignore_case() {
+        let policy = PasswordPolicy {
+            banned_passwords: vec![String::from("Password123")],
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(
+            policy.validate("PASSWORD123"),
+            Err(vec![PasswordViolation::Banned])
+        );
+    }
+
+    #[test]
+    fn checks_min_entropy() {
+        let policy = PasswordPolicy {
+            min_entropy_bits: Some(60.0),
+            ..PasswordPolicy::default()
+        };
+
+        let violations = policy.validate("aaaaaaaa").unwrap_err();
+        assert!(matches!(
+            violations.as_slice(),
+            [PasswordViolation::LowEntropy { .. }]
+        ));
+        assert!(policy.validate("v9#Lm2!xQ7rT").is_ok());
+    }
+
+    #[test]
+    fn estimates_entropy_from_character_classes() {
+        assert_eq!(estimate_entropy_bits(""), 0.0);
+        assert_eq!(estimate_entropy_bits("ab"), 2.0 * 26f64.log2());
+        assert_eq!(estimate_entropy_bits("aB1"), 3.0 * 62f64.log2());
+    }
+
+    #[cfg(feature = "fixtures")]
+    #[test]
+    fn skips_the_breach_rule_without_a_source_unless_required() {
+        use crate::fixtures::{ReplayMode, Replayer};
+        use futures_util::FutureExt;
+
+        let mut client = HashGateClient::replaying(Replayer::new(Vec::new(), ReplayMode::Matching));
+        client.set_password_policy(Some(PasswordPolicy {
+            reject_breached: true,
+            ..PasswordPolicy::default()
+        }));
+
+        let check = |client: &HashGateClient, password| {
+            client.check_password(password).now_or_never().unwrap()
+        };
+        assert!(check(&client, "hunter22").is_ok());
+        assert!(matches!(
+            check(&client, "hunter2"),
+            Err(HashGateError::WeakPassword(_))
+        ));
+
+        client.set_require_breach_check(true);
+        assert!(matches!(
+            check(&client, "hunter22"),
+            Err(HashGateError::BreachCheck(_))
+        ));
+    }
+}
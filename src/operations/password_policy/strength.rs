@@ -0,0 +1,194 @@
+use crate::operations::password_policy::estimate_entropy_bits;
+use serde::{Deserialize, Serialize};
+
+/// Common passwords and fragments that make a password easy to guess.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "123456", "12345678", "qwerty", "abc123", "letmein", "welcome", "monkey", "dragon",
+    "football", "iloveyou", "admin", "login", "princess", "sunshine", "master", "shadow",
+    "baseball", "trustno1", "passw0rd",
+];
+
+/// Keyboard rows that people walk along when picking passwords.
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm", "1234567890"];
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// An estimate of how hard a password is to guess.
+pub struct PasswordStrength {
+    /// A score from 0 (too guessable) to 4 (very unguessable), like zxcvbn.
+    pub score: u8,
+
+    /// The estimated number of guesses needed, as a power of 10.
+    pub guesses_log10: f64,
+
+    /// Suggestions for making the password stronger.
+    pub feedback: Vec<String>,
+}
+
+/// Estimate the strength of a password.
+///
+/// This is a lightweight zxcvbn style estimator, it starts from the password's
+/// entropy and penalizes common passwords, repeats, sequences, keyboard walks
+/// and anything from `user_inputs` (ex: the username or email).
+pub fn estimate_strength(password: &str, user_inputs: &[&str]) -> PasswordStrength {
+    let lowercase = password.to_lowercase();
+    let mut bits = estimate_entropy_bits(password);
+    let mut feedback = Vec::new();
+
+    if COMMON_PASSWORDS
+        .iter()
+        .any(|common| lowercase.contains(common))
+    {
+        bits /= 4.0;
+        feedback.push(String::from("Avoid common passwords and words"));
+    }
+    if user_inputs.iter().any(|input| {
+        let input = input.to_lowercase();
+        input.len() >= 3 && lowercase.contains(&input)
+    }) {
+        bits /= 2.0;
+        feedback.push(String::from("Avoid using your name, username or email"));
+    }
+
+    let repeated = count_runs(password, |a, b| (a == b).then_some(0));
+    if repeated > 0 {
+        bits -= repeated as f64 * 3.0;
+        feedback.push(String::from("Avoid repeated characters like \"aaa\""));
+    }
+
+    let sequential = count_runs(&lowercase, |a, b| {
+        let step = b as i64 - a as i64;
+        (step.abs() == 1 && a.is_ascii_alphanumeric() && b.is_ascii_alphanumeric()).then_some(step)
+    });
+    if sequential > 0 {
+        bits -= sequential as f64 * 3.0;
+        feedback.push(String::from("Avoid sequences like \"abc\" or \"123\""));
+    }
+
+    if has_keyboard_walk(&lowercase) {
+        bits -= 10.0;
+        feedback.push(String::from("Avoid keyboard patterns like \"qwerty\""));
+    }
+
+    let guesses_log10 = bits.max(0.0) * std::f64::consts::LOG10_2;
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    if score < 3 && feedback.is_empty() {
+        feedback.push(String::from("Add more words or characters"));
+    }
+
+    PasswordStrength {
+        score,
+        guesses_log10,
+        feedback,
+    }
+}
+
+/// Count the characters continuing a run of at least 3, where every neighbouring
+/// pair of characters takes the same step (ex: `+1` in "abc", `-1` in "cba").
+fn count_runs(password: &str, step: impl Fn(char, char) -> Option<i64>) -> usize {
+    let chars: Vec<char> = password.chars().collect();
+    let mut count = 0;
+    let mut run = 1;
+    let mut last_step = None;
+
+    for pair in chars.windows(2) {
+        let step = step(pair[0], pair[1]);
+        if step.is_some() && step == last_step {
+            run += 1;
+            if run >= 3 {
+                count += 1;
+            }
+        } else if step.is_some() {
+            run = 2;
+        } else {
+            run = 1;
+        }
+        last_step = step;
+    }
+
+    count
+}
+
+/// Check if a password contains 4 or more neighbouring keys from a keyboard row.
+fn has_keyboard_walk(password: &str) -> bool {
+    password.as_bytes().windows(4).any(|window| {
+        std::str::from_utf8(window).is_ok_and(|window| {
+            KEYBOARD_ROWS.iter().any(|row| {
+                let reversed: String = row.chars().rev().collect();
+                row.contains(window) || reversed.contains(window)
+            })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequential(password: &str) -> usize {
+        count_runs(password, |a, b| {
+            let step = b as i64 - a as i64;
+            (step.abs() == 1).then_some(step)
+        })
+    }
+
+    #[test]
+    fn sequences_keep_one_direction() {
+        assert_eq!(sequential("abcd"), 2);
+        assert_eq!(sequential("4321"), 2);
+        assert_eq!(sequential("abab"), 0);
+        assert_eq!(sequential("1213"), 0);
+        // "abc" and "cba", not one run of 5
+        assert_eq!(sequential("abcba"), 2);
+    }
+
+    #[test]
+    fn repeats_need_three_in_a_row() {
+        let repeated = |password| count_runs(password, |a, b| (a == b).then_some(0));
+
+        assert_eq!(repeated("aab"), 0);
+        assert_eq!(repeated("aaab"), 1);
+        assert_eq!(repeated("aaaa"), 2);
+    }
+
+    #[test]
+    fn detects_keyboard_walks() {
+        assert!(has_keyboard_walk("xqwerx"));
+        assert!(has_keyboard_walk("7654"));
+        assert!(!has_keyboard_walk("qwe"));
+        assert!(!has_keyboard_walk("qazwsx"));
+    }
+
+    #[test]
+    fn scores_weak_passwords_low() {
+        assert_eq!(estimate_strength("password", &[]).score, 0);
+        assert_eq!(estimate_strength("", &[]).score, 0);
+        assert!(
+            estimate_strength("antonio1990", &["antonio"]).score
+                < estimate_strength("antonio1990", &[]).score
+        );
+    }
+
+    #[test]
+    fn scores_strong_passwords_high() {
+        let strength = estimate_strength("v9#Lm2!xQ7rT@p4Z", &[]);
+
+        assert_eq!(strength.score, 4);
+        assert!(strength.feedback.is_empty());
+    }
+
+    #[test]
+    fn gives_feedback_for_each_weakness() {
+        let feedback = estimate_strength("antonio-abc-zzz", &["antonio"]).feedback;
+
+        assert!(feedback.iter().any(|f| f.contains("username")));
+        assert!(feedback.iter().any(|f| f.contains("sequences")));
+        assert!(feedback.iter().any(|f| f.contains("repeated")));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{client::HashGateClient, error::HashGateError, operations::delivery::DeliveryMethod};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A password reset that has been initiated, but not yet verified.
+pub struct Initiated {
+    /// The uuid of the verification session.
+    pub verification_session_id: Uuid,
+
+    /// The verification code to send to the user,
+    /// only set when using `DeliveryMethod::ReturnCode`.
+    ///
+    /// NOTE: The code is never serialized, so storing the state can't leak it.
+    #[serde(skip_serializing, default)]
+    pub verification_code: Option<String>,
+
+    /// When the verification session expires, if HashGate reported it.
+    pub expires_at: Option<NaiveDateTime>,
+
+    /// How many verification attempts are left, if HashGate reported it.
+    pub attempts_remaining: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A password reset where the user has proven they are in fact the user.
+pub struct Verified {
+    /// The uuid of the password reset session.
+    pub password_reset_session_id: Uuid,
+
+    /// When the password reset session expires, if HashGate reported it.
+    pub expires_at: Option<NaiveDateTime>,
+
+    /// The idempotency key the reset is completed with, so retrying
+    /// `PasswordReset::complete()` can't reset the password twice.
+    #[serde(default = "Uuid::new_v4")]
+    pub idempotency_key: Uuid,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A password reset that has been completed.
+pub struct Completed {
+    /// The message from HashGate.
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A typed multi step password reset flow.
+///
+/// Each step returns the next state, so the steps can't be called out of order.
+/// A step that fails leaves its state as it was, so it can be retried. The state
+/// is serializable so it can be stored between HTTP requests and picked back up later.
+///
+/// WARNING: Only store the state server side (ex: a server side session store),
+/// the session ids in it are enough to continue the reset, so it must never
+/// end up in a client side cookie.
+pub struct PasswordReset<S> {
+    /// The current state of the password reset.
+    pub state: S,
+}
+
+#[derive(Debug)]
+/// The outcome of verifying a password reset.
+pub enum PasswordResetVerification {
+    /// The code was correct, the password can now be reset.
+    Verified(PasswordReset<Verified>),
+
+    /// The code was wrong, the reset can be verified again while attempts remain
+    /// (the attempts left are updated on the `PasswordReset<Initiated>`).
+    Retry,
+}
+
+/// Check if a session has expired based on HashGate's reported expiry.
+fn is_expired(expires_at: Option<NaiveDateTime>) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at <= Utc::now().naive_utc())
+}
+
+impl PasswordReset<Initiated> {
+    /// Initiate a password reset for a user.
+    ///
+    /// NOTE: You need to verify that whoever is
+    /// requesting the reset is in fact the user.
+    pub async fn init(
+        client: &mut HashGateClient,
+        email: &str,
+        delivery: &DeliveryMethod,
+    ) -> Result<PasswordReset<Initiated>, HashGateError> {
+        let resp = client.init_password_reset(email, delivery).await?;
+
+        Ok(PasswordReset {
+            state: Initiated {
+                verification_session_id: resp.verification_session_id,
+                verification_code: resp.verification_code,
+                expires_at: resp.expires_at,
+                attempts_remaining: resp.max_attempts,
+            },
+        })
+    }
+
+    /// Check if the verification session has expired.
+    pub fn is_expired(&self) -> bool {
+        is_expired(self.state.expires_at)
+    }
+
+    /// Verify the password reset with the code the user gives to you.
+    pub async fn verify(
+        &mut self,
+        client: &mut HashGateClient,
+        verification_code: &str,
+    ) -> Result<PasswordResetVerification, HashGateError> {
+        if self.is_expired() {
+            return Err(HashGateError::SessionExpired);
+        }
+        if self.state.attempts_remaining == Some(0) {
+            return Err(HashGateError::TooManyAttempts { request_id: None });
+        }
+
+        let resp = client
+            .verify_password_reset(&self.state.verification_session_id, verification_code)
+            .await?;
+
+        match resp.password_reset_session_id {
+            Some(password_reset_session_id) => {
+                Ok(PasswordResetVerification::Verified(PasswordReset {
+                    state: Verified {
+                        password_reset_session_id,
+                        expires_at: resp.expires_at,
+                        idempotency_key: Uuid::new_v4(),
+                    },
+                }))
+            }
+            None => {
+                self.state.attempts_remaining = resp
+                    .attempts_remaining
+                    .or(self.state.attempts_remaining.map(|n| n.saturating_sub(1)));
+
+                Ok(PasswordResetVerification::Retry)
+            }
+        }
+    }
+}
+
+impl PasswordReset<Verified> {
+    /// Check if the password reset session has expired.
+    pub fn is_expired(&self) -> bool {
+        is_expired(self.state.expires_at)
+    }
+
+    /// Reset the user's password, this is the final step in the password reset.
+    ///
+    /// NOTE: Retries reuse the state's idempotency key, so a retry must send the same password.
+    pub async fn complete(
+        &self,
+        client: &mut HashGateClient,
+        new_password: &str,
+    ) -> Result<PasswordReset<Completed>, HashGateError> {
+        if self.is_expired() {
+            return Err(HashGateError::SessionExpired);
+        }
+
+        let (resp, request_id) = client
+            .try_reset_user_password(
+                &self.state.password_reset_session_id,
+                new_password,
+                Some(&self.state.idempotency_key.to_string()),
+            )
+            .await?;
+        if !resp.was_successful {
+            return Err(HashGateError::ServerError { request_id });
+        }
+
+        Ok(PasswordReset {
+            state: Completed {
+                message: resp.message,
+            },
+        })
+    }
+}
+
+#[cfg(all(test, feature = "fixtures"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Exchange, ReplayMode, Replayer};
+    use futures_util::FutureExt;
+
+    fn exchange(endpoint: &str, status: u16, response: serde_json::Value) -> Exchange {
+        Exchange {
+            method: String::from("POST"),
+            endpoint: endpoint.to_owned(),
+            request: None,
+            status,
+            response,
+        }
+    }
+
+    #[test]
+    fn failed_steps_keep_their_state() {
+        let password_reset_session_id = Uuid::new_v4();
+        let exchanges = vec![
+            exchange("user/verify-password-reset", 503, serde_json::json!({})),
+            exchange(
+                "user/verify-password-reset",
+                200,
+                serde_json::json!({
+                    "passwordResetSessionId": password_reset_session_id,
+                    "message": "Verified",
+                    "wasSuccessful": true,
+                }),
+            ),
+            exchange(
+                "user/reset-password",
+                200,
+                serde_json::json!({ "message": "Failed", "wasSuccessful": false }),
+            ),
+            exchange(
+                "user/reset-password",
+                200,
+                serde_json::json!({ "message": "Reset", "wasSuccessful": true }),
+            ),
+        ];
+        let mut client = HashGateClient::replaying(Replayer::new(exchanges, ReplayMode::InOrder));
+
+        let mut reset = PasswordReset {
+            state: Initiated {
+                verification_session_id: Uuid::new_v4(),
+                verification_code: None,
+                expires_at: None,
+                attempts_remaining: Some(3),
+            },
+        };
+
+        let unavailable = reset.verify(&mut client, "123456").now_or_never().unwrap();
+        assert!(matches!(
+            unavailable,
+            Err(HashGateError::ServerUnavailable { .. })
+        ));
+        assert_eq!(reset.state.attempts_remaining, Some(3));
+
+        let Ok(PasswordResetVerification::Verified(verified)) =
+            reset.verify(&mut client, "123456").now_or_never().unwrap()
+        else {
+            panic!("verifying the reset failed");
+        };
+
+        let failed = verified
+            .complete(&mut client, "hunter22")
+            .now_or_never()
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(failed, HashGateError::ServerError { .. }));
+        assert!(failed.request_id().is_some());
+
+        let completed = verified
+            .complete(&mut client, "hunter22")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(completed.state.message, "Reset");
+    }
+}
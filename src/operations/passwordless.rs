@@ -0,0 +1,110 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::delivery::DeliveryMethod,
+    types::{
+        identity::Email,
+        requests::{CompletePasswordlessSignInReq, InitPasswordlessSignInReq},
+        responses::{AuthResponse, InitPasswordlessSignInResp},
+    },
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+/// Options for a passwordless sign in session.
+pub struct PasswordlessOptions {
+    /// The number of digits in the one time code.
+    pub code_length: u8,
+
+    /// How long the session lives for in seconds.
+    pub ttl_seconds: u32,
+
+    /// The maximum number of attempts before the session is burned.
+    pub max_attempts: u32,
+
+    /// An optional base url to build a magic link from, the session id
+    /// and code get appended to it as query parameters by HashGate.
+    pub magic_link_base_url: Option<String>,
+
+    /// How the code gets to the user.
+    pub delivery: DeliveryMethod,
+}
+impl Default for PasswordlessOptions {
+    fn default() -> Self {
+        PasswordlessOptions {
+            code_length: 6,
+            ttl_seconds: 600,
+            max_attempts: 5,
+            magic_link_base_url: None,
+            delivery: DeliveryMethod::default(),
+        }
+    }
+}
+
+impl HashGateClient {
+    /// Initialize a passwordless sign in session for a user.
+    ///
+    /// This reuses the verification session machinery, so by default HashGate
+    /// delivers the code (or magic link) to the user itself. Use the verification
+    /// session id to then complete the sign in with the code the user gives to you.
+    pub async fn init_passwordless_sign_in(
+        &mut self,
+        email: &str,
+        options: &PasswordlessOptions,
+    ) -> Result<InitPasswordlessSignInResp, HashGateError> {
+        let endpoint = "user/init-passwordless-sign-in";
+
+        let payload = InitPasswordlessSignInReq {
+            email: Email::parse(email)?,
+            code_length: options.code_length,
+            ttl_seconds: options.ttl_seconds,
+            max_attempts: options.max_attempts,
+            magic_link_base_url: options.magic_link_base_url.clone(),
+            delivery: (&options.delivery).into(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+
+        if resp.status().is_success() {
+            let resp_body = resp.json::<InitPasswordlessSignInResp>().await?;
+            Ok(resp_body)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Complete a passwordless sign in session, returning the user's auth token.
+    ///
+    /// NOTE: You must have a verification session id to call this
+    /// you can obtain one from `HashGateClient::init_passwordless_sign_in()`.
+    pub async fn complete_passwordless_sign_in(
+        &mut self,
+        verification_session_id: &Uuid,
+        verification_code: &str,
+    ) -> Result<String, HashGateError> {
+        let endpoint = "user/complete-passwordless-sign-in";
+
+        let payload = CompletePasswordlessSignInReq {
+            verification_session_id: verification_session_id.to_owned(),
+            verification_code: verification_code.to_owned(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+
+        if resp.status().is_success() {
+            let resp_body = resp.json::<AuthResponse>().await?;
+            if let Some(token) = resp_body.token {
+                Ok(token)
+            } else {
+                Err(HashGateError::FailedSignIn { request_id })
+            }
+        } else if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            Err(HashGateError::TooManyAttempts { request_id })
+        } else {
+            Err(HashGateError::FailedSignIn { request_id })
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    types::responses::GetPoolResp,
+};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -31,3 +36,21 @@ pub struct Pool {
     /// The creation date of the user pool.
     pub creation_date: NaiveDateTime,
 }
+
+impl HashGateClient {
+    /// Get the user pool the client belongs to.
+    pub async fn get_pool(&mut self) -> Result<Pool, HashGateError> {
+        let endpoint = "pool/info";
+
+        let resp = self.get(endpoint).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetPoolResp>().await?;
+            resp_body
+                .pool
+                .ok_or(HashGateError::ServerError { request_id })
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    operations::{group::ADMIN_GROUP_ID, user::User},
+    types::{
+        identity::{Email, Username},
+        requests::UserRegistrationReq,
+        responses::CreateUserResp,
+    },
+};
+
+/// A fluent builder for registering a user into your user pool.
+///
+/// ```ignore
+/// let user = client
+///     .register("antonio", "hunter2")
+///     .email("antonio@example.com")
+///     .group(5)
+///     .attribute("plan", json!("pro"))
+///     .send_verification(true)
+///     .send()
+///     .await?;
+/// ```
+pub struct RegisterUser<'a> {
+    client: &'a mut HashGateClient,
+    username: String,
+    password: String,
+    email: Option<String>,
+    group_ids: Vec<i32>,
+    custom_attributes: serde_json::Map<String, serde_json::Value>,
+    send_verification: bool,
+    idempotency_key: Option<String>,
+}
+impl<'a> RegisterUser<'a> {
+    /// Set the email of the user.
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_owned());
+        self
+    }
+
+    /// Add the user to a group, can be called multiple times.
+    pub fn group(mut self, group_id: i32) -> Self {
+        if !self.group_ids.contains(&group_id) {
+            self.group_ids.push(group_id);
+        }
+        self
+    }
+
+    /// Add the user to the admin group.
+    pub fn admin(self) -> Self {
+        self.group(ADMIN_GROUP_ID)
+    }
+
+    /// Set an initial custom attribute for the user, can be called multiple times.
+    pub fn attribute(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.custom_attributes.insert(key.to_owned(), value);
+        self
+    }
+
+    /// Have HashGate send the user a verification code once they're created.
+    pub fn send_verification(mut self, send_verification: bool) -> Self {
+        self.send_verification = send_verification;
+        self
+    }
+
+    /// Set an idempotency key, so retrying the registration with
+    /// the same key can't create the user twice.
+    pub fn idempotency_key(mut self, idempotency_key: &str) -> Self {
+        self.idempotency_key = Some(idempotency_key.to_owned());
+        self
+    }
+
+    /// Register the user, returning the created `User`.
+    ///
+    /// NOTE: The username and email are validated and normalized
+    /// (see `Username` and `Email`) before anything is sent.
+    pub async fn send(self) -> Result<User, HashGateError> {
+        let endpoint = "user/create";
+
+        let username = Username::parse(&self.username)?;
+        let email = self.email.as_deref().map(Email::parse).transpose()?;
+        self.client.check_password(&self.password).await?;
+
+        let custom_attributes = if self.custom_attributes.is_empty() {
+            None
+        } else {
+            Some(serde_json::Value::Object(self.custom_attributes))
+        };
+
+        let payload = UserRegistrationReq {
+            username,
+            email,
+            password: self.password,
+            group_id: self.group_ids.first().copied(),
+            group_ids: self.group_ids,
+            custom_attributes,
+            send_verification: self.send_verification,
+        };
+
+        let resp = self
+            .client
+            .post_idempotent(endpoint, &payload, self.idempotency_key.as_deref())
+            .await?;
+        let request_id = request_id(&resp);
+
+        if resp.status().is_success() {
+            let resp_body = resp.json::<CreateUserResp>().await?;
+            resp_body
+                .user
+                .ok_or(HashGateError::ServerError { request_id })
+        } else if resp.status() == reqwest::StatusCode::CONFLICT {
+            Err(HashGateError::UsernameTaken { request_id })
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+impl HashGateClient {
+    /// Start registering a user into your user pool.
+    pub fn register(&mut self, username: &str, password: &str) -> RegisterUser<'_> {
+        RegisterUser {
+            client: self,
+            username: username.to_owned(),
+            password: password.to_owned(),
+            email: None,
+            group_ids: Vec::new(),
+            custom_attributes: serde_json::Map::new(),
+            send_verification: false,
+            idempotency_key: None,
+        }
+    }
+}
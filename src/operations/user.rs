@@ -1,16 +1,19 @@
 use crate::{
-    client::HashGateClient,
+    client::{request_id, HashGateClient},
     error::HashGateError,
+    operations::{delivery::DeliveryMethod, mfa::SignInOutcome},
     types::{
+        identity::Email,
         requests::{self, InitPasswordResetReq, ResetPasswordReq, VerifyPasswordResetReq},
         responses::{
-            self, CreateUserResp, InitPasswordResetResp, InitVerificationResp, ResetPasswordResp,
+            self, InitPasswordResetResp, InitVerificationResp, ResetPasswordResp,
             UpdateUserPasswordResp, VerificationResp, VerifyPasswordResetResp,
         },
     },
 };
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use uuid::Uuid;
 
 #[derive(Deserialize, Debug, Serialize, Clone)]
@@ -25,9 +28,29 @@ pub struct User {
     /// The email of the user.
     pub email: Option<String>,
 
+    /// The phone number of the user, if they have one.
+    #[serde(default)]
+    pub phone_number: Option<String>,
+
     /// The verification status of the user.
     pub is_verified: bool,
 
+    /// The group the user belongs to, if any.
+    #[serde(default)]
+    pub group_id: Option<i32>,
+
+    /// All the groups the user belongs to.
+    #[serde(default)]
+    pub group_ids: Vec<i32>,
+
+    /// The permissions granted to the user through their groups.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+
+    /// When the user's lockout ends, if they're locked out.
+    #[serde(default)]
+    pub locked_until: Option<NaiveDateTime>,
+
     /// The creation date of the user.
     pub creation_date: NaiveDateTime,
 
@@ -48,11 +71,12 @@ impl User {
 
         match client.post(endpoint, &payload).await {
             Ok(resp) => {
+                let request_id = request_id(&resp);
                 let resp_body = resp.json::<responses::GetUserResp>().await?;
                 if let Some(user) = resp_body.user {
                     Ok(user)
                 } else {
-                    Err(HashGateError::UserNotFound)
+                    Err(HashGateError::UserNotFound { request_id })
                 }
             }
             Err(e) => Err(e),
@@ -71,16 +95,17 @@ impl User {
         };
 
         let resp = client.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
 
         if resp.status().is_success() {
             let resp_body = resp.json::<responses::GetUserResp>().await?;
             if let Some(user) = resp_body.user {
                 Ok(user)
             } else {
-                Err(HashGateError::UserNotFound)
+                Err(HashGateError::UserNotFound { request_id })
             }
         } else {
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
 
@@ -91,18 +116,21 @@ impl User {
         key: &str,
         value: &serde_json::Value,
     ) -> Result<(), HashGateError> {
-        let endpoint = "user/set-attribute";
-
-        let payload = requests::SetUserCustomAttributeReq {
-            user_id: self.id,
-            key: key.to_string(),
-            value: value.clone(),
-        };
+        client.set_user_attribute(&self.id, key, value, None).await
+    }
 
-        match client.post(endpoint, &payload).await {
-            Ok(_) => Ok(()),
-            Err(_) => Err(HashGateError::CouldNotSetAttribute),
-        }
+    /// Set a custom attribute for the `User` the same as `User::set_custom_attribute()`,
+    /// with an idempotency key so retrying with the same key only sets it once.
+    pub async fn set_custom_attribute_idempotent(
+        &self,
+        client: &mut HashGateClient,
+        key: &str,
+        value: &serde_json::Value,
+        idempotency_key: &str,
+    ) -> Result<(), HashGateError> {
+        client
+            .set_user_attribute(&self.id, key, value, Some(idempotency_key))
+            .await
     }
 
     /// Get all custom attributes for the `User`.
@@ -110,20 +138,7 @@ impl User {
         &self,
         client: &mut HashGateClient,
     ) -> Result<serde_json::Value, HashGateError> {
-        let endpoint = "user/get-attributes";
-
-        let payload = requests::GetUserCustomAttributesReq { user_id: self.id };
-
-        match client.post(endpoint, &payload).await {
-            Ok(resp) => {
-                let resp_body = resp
-                    .json::<responses::GetUserCustomAttributesResp>()
-                    .await?;
-
-                Ok(resp_body.attributes)
-            }
-            Err(e) => Err(e),
-        }
+        client.get_user_attributes(&self.id).await
     }
 
     /// Get a specific custom attribute for the `User`.
@@ -151,39 +166,146 @@ impl User {
 
     /// Initialize a verification session for the `User`.
     ///
-    /// This code can be sent to the user in email or phone sms/call.
+    /// By default HashGate delivers the code to the user itself, and only the
+    /// verification session id is returned. Use `DeliveryMethod::ReturnCode`
+    /// to get the code back and send it to the user yourself.
     /// Use the verification session id, to then verify the code the user gives to you.
     pub async fn init_verification(
         &mut self,
         client: &mut HashGateClient,
+        delivery: &DeliveryMethod,
+    ) -> Result<InitVerificationResp, HashGateError> {
+        client.init_user_verification(&self.id, delivery).await
+    }
+
+    /// Update the `User`s password.
+    pub async fn update_password(
+        &mut self,
+        new_password: String,
+        client: &mut HashGateClient,
+    ) -> Result<UpdateUserPasswordResp, HashGateError> {
+        client
+            .update_user_password(&self.id, new_password, None)
+            .await
+    }
+
+    /// Update the `User`s password the same as `User::update_password()`,
+    /// with an idempotency key so retrying with the same key only updates it once.
+    pub async fn update_password_idempotent(
+        &mut self,
+        new_password: String,
+        client: &mut HashGateClient,
+        idempotency_key: &str,
+    ) -> Result<UpdateUserPasswordResp, HashGateError> {
+        client
+            .update_user_password(&self.id, new_password, Some(idempotency_key))
+            .await
+    }
+}
+
+impl HashGateClient {
+    /// Set a custom attribute for a user.
+    pub(crate) async fn set_user_attribute(
+        &mut self,
+        user_id: &Uuid,
+        key: &str,
+        value: &serde_json::Value,
+        idempotency_key: Option<&str>,
+    ) -> Result<(), HashGateError> {
+        let endpoint = "user/set-attribute";
+
+        let payload = requests::SetUserCustomAttributeReq {
+            user_id: *user_id,
+            key: key.to_string(),
+            value: value.clone(),
+        };
+
+        match self
+            .post_idempotent(endpoint, &payload, idempotency_key)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(HashGateError::CouldNotSetAttribute {
+                request_id: e.request_id().map(str::to_owned),
+            }),
+        }
+    }
+
+    /// Get all custom attributes for a user.
+    pub(crate) async fn get_user_attributes(
+        &mut self,
+        user_id: &Uuid,
+    ) -> Result<serde_json::Value, HashGateError> {
+        let endpoint = "user/get-attributes";
+
+        let payload = requests::GetUserCustomAttributesReq { user_id: *user_id };
+
+        match self.post(endpoint, &payload).await {
+            Ok(resp) => {
+                let resp_body = resp
+                    .json::<responses::GetUserCustomAttributesResp>()
+                    .await?;
+
+                Ok(resp_body.attributes)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Initialize a verification session for a user.
+    pub(crate) async fn init_user_verification(
+        &mut self,
+        user_id: &Uuid,
+        delivery: &DeliveryMethod,
     ) -> Result<InitVerificationResp, HashGateError> {
+        self.try_init_user_verification(user_id, delivery)
+            .await
+            .map(|(resp, _)| resp)
+    }
+
+    /// Initialize a verification session for a user, also returning the request id.
+    pub(crate) async fn try_init_user_verification(
+        &mut self,
+        user_id: &Uuid,
+        delivery: &DeliveryMethod,
+    ) -> Result<(InitVerificationResp, Option<String>), HashGateError> {
         let endpoint = "user/init-verification";
 
-        let payload = requests::InitVerificationReq { user_id: self.id };
+        let payload = requests::InitVerificationReq {
+            user_id: *user_id,
+            delivery: delivery.into(),
+        };
 
-        let resp = client.post(endpoint, &payload).await?;
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
         if resp.status().is_success() {
             let resp_body = resp.json::<InitVerificationResp>().await?;
-            Ok(resp_body)
+            Ok((resp_body, request_id))
         } else {
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
 
-    /// Update the `User`s password.
-    pub async fn update_password(
+    /// Update a user's password.
+    pub(crate) async fn update_user_password(
         &mut self,
+        user_id: &Uuid,
         new_password: String,
-        client: &mut HashGateClient,
+        idempotency_key: Option<&str>,
     ) -> Result<UpdateUserPasswordResp, HashGateError> {
         let endpoint = "user/update-password";
 
+        self.check_password(&new_password).await?;
+
         let payload = requests::UpdateUserPasswordReq {
-            user_id: self.id,
+            user_id: *user_id,
             new_password,
         };
 
-        let resp = client.post(endpoint, &payload).await?;
+        let resp = self
+            .post_idempotent(endpoint, &payload, idempotency_key)
+            .await?;
+        let request_id = request_id(&resp);
 
         if resp.status().is_success() {
             let resp_body = resp.json::<UpdateUserPasswordResp>().await?;
@@ -191,59 +313,108 @@ impl User {
         } else {
             // The only reason this can fail is due to api down I think
             // TODO: look into above
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
-}
 
-impl HashGateClient {
     /// Authenticate a user in your user pool with a given username and password.
+    ///
+    /// NOTE: The username is sent as given (only trimmed), the `Username` rules
+    /// are only enforced when registering.
+    ///
+    /// NOTE: Users with MFA enabled get back `SignInOutcome::MfaRequired`,
+    /// which must be completed with `HashGateClient::complete_mfa_challenge()`.
+    ///
+    /// Locked out users get back `HashGateError::AccountLocked`.
     pub async fn authenticate_user(
         &mut self,
         username: String,
         password: String,
-    ) -> Result<String, HashGateError> {
+    ) -> Result<SignInOutcome, HashGateError> {
+        self.try_sign_in(username, password, None).await
+    }
+
+    /// Authenticate a user the same as `HashGateClient::authenticate_user()`, also
+    /// throttling by the IP the attempt came from when a `LoginThrottle` is set.
+    pub async fn authenticate_user_from_ip(
+        &mut self,
+        username: String,
+        password: String,
+        ip: IpAddr,
+    ) -> Result<SignInOutcome, HashGateError> {
+        self.try_sign_in(username, password, Some(ip)).await
+    }
+
+    async fn try_sign_in(
+        &mut self,
+        username: String,
+        password: String,
+        ip: Option<IpAddr>,
+    ) -> Result<SignInOutcome, HashGateError> {
         let endpoint = "user/sign-in";
 
-        let payload = requests::UserAuthReq { username, password };
+        let attempt = match self.login_throttle() {
+            Some(throttle) => Some(throttle.check(&username, ip)?),
+            None => None,
+        };
+
+        // Usernames are only validated at registration, so accounts created
+        // before (or outside of) the `Username` rules can still sign in
+        let payload = requests::UserAuthReq {
+            username: username.trim().to_owned(),
+            password,
+        };
 
         let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        let status = resp.status();
 
-        if resp.status().is_success() {
+        let outcome = if status.is_success() {
             let resp_body = resp.json::<responses::AuthResponse>().await?;
-            if let Some(token) = resp_body.token {
-                Ok(token)
-            } else {
-                Err(HashGateError::FailedSignIn)
-            }
+            SignInOutcome::try_from_auth_response(resp_body)
+        } else if status == reqwest::StatusCode::LOCKED {
+            let resp_body = resp.json::<responses::AuthResponse>().await.ok();
+            Err(HashGateError::AccountLocked {
+                until: resp_body.and_then(|resp_body| resp_body.locked_until),
+            })
         } else {
-            Err(HashGateError::FailedSignIn)
+            Err(HashGateError::FailedSignIn { request_id })
+        };
+
+        // A sign in waiting on MFA and any other error drop the attempt, releasing
+        // its reservation, MFA completion is throttled as part of the same sign in
+        if let Some(attempt) = attempt {
+            match &outcome {
+                Ok(SignInOutcome::Authenticated { .. }) => attempt.succeeded(),
+                Ok(SignInOutcome::MfaRequired { challenge_id }) => {
+                    if let Some(throttle) = self.login_throttle() {
+                        throttle.start_challenge(*challenge_id, &username, ip);
+                    }
+                }
+                Err(HashGateError::FailedSignIn { .. }) => attempt.failed(),
+                Err(_) => {}
+            }
         }
+
+        outcome
     }
 
     /// Register a user into your user pool.
+    ///
+    /// NOTE: Use `HashGateClient::register()` to also set groups,
+    /// initial custom attributes, or send a verification code.
     pub async fn register_user(
         &mut self,
         username: String,
         email: Option<String>,
         password: String,
-    ) -> Result<CreateUserResp, HashGateError> {
-        let endpoint = "user/create";
-
-        let payload = requests::UserRegistrationReq {
-            username,
-            email,
-            password,
-            group_id: None,
-        };
-
-        let resp = self.post(endpoint, &payload).await?;
-        if resp.status().is_success() {
-            let resp_body = resp.json::<responses::CreateUserResp>().await?;
-            Ok(resp_body)
-        } else {
-            Err(HashGateError::ServerError)
+    ) -> Result<User, HashGateError> {
+        let mut registration = self.register(&username, &password);
+        if let Some(email) = email {
+            registration = registration.email(&email);
         }
+
+        registration.send().await
     }
 
     /// Create an admin user in your user pool.
@@ -252,28 +423,13 @@ impl HashGateClient {
         username: String,
         email: Option<String>,
         password: String,
-    ) -> Result<String, HashGateError> {
-        let endpoint = "user/create";
-
-        let payload = requests::UserRegistrationReq {
-            username,
-            email,
-            password,
-            group_id: Some(1),
-        };
-
-        let resp = self.post(endpoint, &payload).await?;
-
-        if resp.status().is_success() {
-            let resp_body = resp.json::<responses::AuthResponse>().await?;
-            if let Some(token) = resp_body.token {
-                Ok(token)
-            } else {
-                Err(HashGateError::FailedSignIn)
-            }
-        } else {
-            Err(HashGateError::FailedSignIn)
+    ) -> Result<User, HashGateError> {
+        let mut registration = self.register(&username, &password).admin();
+        if let Some(email) = email {
+            registration = registration.email(&email);
         }
+
+        registration.send().await
     }
 
     /// Initialize a password reset for a user.
@@ -282,24 +438,41 @@ impl HashGateClient {
     /// requesting the reset is in fact the user.
     /// Email, or phone is recommended, but you could
     /// self verify if you want however it's not recommended.
+    /// The code is only returned when using `DeliveryMethod::ReturnCode`.
     pub async fn init_password_reset(
         &mut self,
         email: &str,
+        delivery: &DeliveryMethod,
     ) -> Result<InitPasswordResetResp, HashGateError> {
+        self.try_init_password_reset(email, delivery)
+            .await
+            .map(|(resp, _)| resp)
+    }
+
+    /// Initialize a password reset for a user, also returning the request id.
+    pub(crate) async fn try_init_password_reset(
+        &mut self,
+        email: &str,
+        delivery: &DeliveryMethod,
+    ) -> Result<(InitPasswordResetResp, Option<String>), HashGateError> {
         let endpoint = "user/init-password-reset";
 
-        let email = email.to_owned();
-        let payload = InitPasswordResetReq { email };
+        let email = Email::parse(email)?;
+        let payload = InitPasswordResetReq {
+            email,
+            delivery: delivery.into(),
+        };
 
         let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
 
         if resp.status().is_success() {
             let resp_body = resp.json::<InitPasswordResetResp>().await?;
-            Ok(resp_body)
+            Ok((resp_body, request_id))
         } else {
             // The only reason this can fail is due to api down I think
             // TODO: look into above
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
 
@@ -325,6 +498,7 @@ impl HashGateClient {
         };
 
         let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
 
         if resp.status().is_success() {
             let resp_body = resp.json::<VerifyPasswordResetResp>().await?;
@@ -332,7 +506,7 @@ impl HashGateClient {
         } else {
             // The only reason this can fail is due to api down I think
             // TODO: look into above
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
 
@@ -347,8 +521,41 @@ impl HashGateClient {
         password_reset_session_id: &Uuid,
         new_password: &str,
     ) -> Result<ResetPasswordResp, HashGateError> {
+        self.try_reset_user_password(password_reset_session_id, new_password, None)
+            .await
+            .map(|(resp, _)| resp)
+    }
+
+    /// Reset a users password the same as `HashGateClient::reset_user_password()`,
+    /// with an idempotency key so retrying the reset with the same key only resets it once.
+    ///
+    /// NOTE: Use a new key for every reset, reusing a key for a different write is rejected.
+    pub async fn reset_user_password_idempotent(
+        &mut self,
+        password_reset_session_id: &Uuid,
+        new_password: &str,
+        idempotency_key: &str,
+    ) -> Result<ResetPasswordResp, HashGateError> {
+        self.try_reset_user_password(
+            password_reset_session_id,
+            new_password,
+            Some(idempotency_key),
+        )
+        .await
+        .map(|(resp, _)| resp)
+    }
+
+    /// Reset a users password, also returning the request id of the reset.
+    pub(crate) async fn try_reset_user_password(
+        &mut self,
+        password_reset_session_id: &Uuid,
+        new_password: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<(ResetPasswordResp, Option<String>), HashGateError> {
         let endpoint = "user/reset-password";
 
+        self.check_password(new_password).await?;
+
         let password_reset_session_id = password_reset_session_id.to_owned();
         let new_password = new_password.to_owned();
 
@@ -357,15 +564,18 @@ impl HashGateClient {
             new_password,
         };
 
-        let resp = self.post(endpoint, &payload).await?;
+        let resp = self
+            .post_idempotent(endpoint, &payload, idempotency_key)
+            .await?;
+        let request_id = request_id(&resp);
 
         if resp.status().is_success() {
             let resp_body = resp.json::<ResetPasswordResp>().await?;
-            Ok(resp_body)
+            Ok((resp_body, request_id))
         } else {
             // The only reason this can fail is due to api down I think
             // TODO: look into above
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
         }
     }
 
@@ -383,11 +593,51 @@ impl HashGateClient {
         };
 
         let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
         if resp.status().is_success() {
             let resp_body = resp.json::<VerificationResp>().await?;
             Ok(resp_body)
         } else {
-            Err(HashGateError::ServerError)
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "fixtures"))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Exchange, ReplayMode, Replayer};
+    use futures_util::FutureExt;
+
+    #[test]
+    fn legacy_usernames_reach_sign_in() {
+        let usernames = ["Jo", "ana@example.com", "José"];
+        let exchanges = usernames
+            .iter()
+            .map(|username| Exchange {
+                method: String::from("POST"),
+                endpoint: String::from("user/sign-in"),
+                request: Some(serde_json::json!({
+                    "username": username,
+                    "password": crate::fixtures::SCRUBBED,
+                })),
+                status: 200,
+                response: serde_json::json!({ "token": "abc", "wasSuccessful": true }),
+            })
+            .collect();
+        let mut client = HashGateClient::replaying(Replayer::new(exchanges, ReplayMode::Matching));
+
+        for username in usernames {
+            let outcome = client
+                .authenticate_user(format!(" {username} "), String::from("hunter22"))
+                .now_or_never()
+                .unwrap();
+            assert_eq!(
+                outcome.unwrap(),
+                SignInOutcome::Authenticated {
+                    token: String::from("abc")
+                }
+            );
         }
     }
 }
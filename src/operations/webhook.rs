@@ -0,0 +1,317 @@
+use crate::{
+    client::{request_id, HashGateClient},
+    error::HashGateError,
+    types::{
+        requests::{CreateWebhookReq, TestWebhookReq, UpdateWebhookReq, WebhookIdReq},
+        responses::{
+            DeleteWebhookResp, GetWebhookDeliveriesResp, GetWebhooksResp, TestWebhookResp,
+            WebhookResp,
+        },
+    },
+};
+use chrono::NaiveDateTime;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize, Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+/// The different types of webhook events HashGate sends.
+pub enum WebhookEventType {
+    /// A user was created.
+    #[serde(rename = "user.created")]
+    UserCreated,
+
+    /// A user was verified.
+    #[serde(rename = "user.verified")]
+    UserVerified,
+
+    /// A user reset their password.
+    #[serde(rename = "user.password_reset")]
+    PasswordReset,
+
+    /// A user was deleted.
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+
+    /// An event type this version of the SDK doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A webhook subscription in your user pool.
+pub struct Webhook {
+    /// The uuid of the webhook.
+    pub webhook_id: Uuid,
+
+    /// The url events are delivered to.
+    pub url: String,
+
+    /// The event types delivered to the webhook.
+    pub events: Vec<WebhookEventType>,
+
+    /// If events are currently being delivered to the webhook.
+    pub is_active: bool,
+
+    /// The creation date of the webhook.
+    pub creation_date: NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// An attempt at delivering an event to a webhook.
+pub struct WebhookDelivery {
+    /// The uuid of the delivery.
+    pub delivery_id: Uuid,
+
+    /// The uuid of the event delivered.
+    pub event_id: Uuid,
+
+    /// The type of the event delivered.
+    pub event_type: WebhookEventType,
+
+    /// The status code your endpoint responded with, if it responded.
+    pub status_code: Option<u16>,
+
+    /// How many times the delivery has been retried.
+    pub retry_count: u32,
+
+    /// If the delivery succeeded (a 2xx status code).
+    pub was_successful: bool,
+
+    /// When the delivery was last attempted.
+    pub attempted_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+/// Changes to a webhook, only the fields that are set get updated.
+pub struct WebhookUpdate {
+    /// The new url to deliver events to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// The new event types to deliver.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<WebhookEventType>>,
+
+    /// The new secret to sign deliveries with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Pause or resume deliveries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active: Option<bool>,
+}
+impl std::fmt::Debug for WebhookUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("WebhookUpdate")
+            .field("url", &self.url)
+            .field("events", &self.events)
+            .field("secret", &self.secret.as_ref().map(|_| "[REDACTED]"))
+            .field("is_active", &self.is_active)
+            .finish()
+    }
+}
+
+/// Map a failed webhook response to an error.
+fn webhook_error(resp: &Response) -> HashGateError {
+    let request_id = request_id(resp);
+    if resp.status() == StatusCode::NOT_FOUND {
+        HashGateError::WebhookNotFound { request_id }
+    } else {
+        HashGateError::ServerError { request_id }
+    }
+}
+
+impl HashGateClient {
+    /// Create a webhook subscription, deliveries are signed with the secret
+    /// (see `webhooks::WebhookVerifier` for receiving them).
+    pub async fn create_webhook(
+        &mut self,
+        url: &str,
+        events: &[WebhookEventType],
+        secret: &str,
+    ) -> Result<Webhook, HashGateError> {
+        self.try_create_webhook(url, events, secret, None).await
+    }
+
+    /// Create a webhook subscription the same as `HashGateClient::create_webhook()`,
+    /// with an idempotency key so retrying with the same key only creates it once.
+    pub async fn create_webhook_idempotent(
+        &mut self,
+        url: &str,
+        events: &[WebhookEventType],
+        secret: &str,
+        idempotency_key: &str,
+    ) -> Result<Webhook, HashGateError> {
+        self.try_create_webhook(url, events, secret, Some(idempotency_key))
+            .await
+    }
+
+    async fn try_create_webhook(
+        &mut self,
+        url: &str,
+        events: &[WebhookEventType],
+        secret: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<Webhook, HashGateError> {
+        let endpoint = "webhook/create";
+
+        let payload = CreateWebhookReq {
+            url: url.to_owned(),
+            events: events.to_vec(),
+            secret: secret.to_owned(),
+        };
+
+        let resp = self
+            .post_idempotent(endpoint, &payload, idempotency_key)
+            .await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<WebhookResp>().await?;
+            resp_body
+                .webhook
+                .ok_or(HashGateError::ServerError { request_id })
+        } else {
+            Err(webhook_error(&resp))
+        }
+    }
+
+    /// List all the webhook subscriptions in your user pool.
+    pub async fn list_webhooks(&mut self) -> Result<Vec<Webhook>, HashGateError> {
+        let endpoint = "webhook/list";
+
+        let resp = self.get(endpoint).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetWebhooksResp>().await?;
+            Ok(resp_body.webhooks)
+        } else {
+            Err(HashGateError::ServerError { request_id })
+        }
+    }
+
+    /// Update a webhook subscription.
+    pub async fn update_webhook(
+        &mut self,
+        webhook_id: &Uuid,
+        update: &WebhookUpdate,
+    ) -> Result<Webhook, HashGateError> {
+        let endpoint = "webhook/update";
+
+        let payload = UpdateWebhookReq {
+            webhook_id: webhook_id.to_owned(),
+            update: update.clone(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<WebhookResp>().await?;
+            resp_body
+                .webhook
+                .ok_or(HashGateError::ServerError { request_id })
+        } else {
+            Err(webhook_error(&resp))
+        }
+    }
+
+    /// Delete a webhook subscription.
+    pub async fn delete_webhook(&mut self, webhook_id: &Uuid) -> Result<(), HashGateError> {
+        let endpoint = "webhook/delete";
+
+        let payload = WebhookIdReq {
+            webhook_id: webhook_id.to_owned(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        if resp.status().is_success() {
+            resp.json::<DeleteWebhookResp>().await?;
+            Ok(())
+        } else {
+            Err(webhook_error(&resp))
+        }
+    }
+
+    /// Trigger a sample delivery of an event type to a webhook.
+    pub async fn test_webhook(
+        &mut self,
+        webhook_id: &Uuid,
+        event_type: WebhookEventType,
+    ) -> Result<WebhookDelivery, HashGateError> {
+        let endpoint = "webhook/test";
+
+        let payload = TestWebhookReq {
+            webhook_id: webhook_id.to_owned(),
+            event_type,
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        let request_id = request_id(&resp);
+        if resp.status().is_success() {
+            let resp_body = resp.json::<TestWebhookResp>().await?;
+            resp_body
+                .delivery
+                .ok_or(HashGateError::ServerError { request_id })
+        } else {
+            Err(webhook_error(&resp))
+        }
+    }
+
+    /// Get the delivery history of a webhook, newest first.
+    pub async fn webhook_deliveries(
+        &mut self,
+        webhook_id: &Uuid,
+    ) -> Result<Vec<WebhookDelivery>, HashGateError> {
+        let endpoint = "webhook/deliveries";
+
+        let payload = WebhookIdReq {
+            webhook_id: webhook_id.to_owned(),
+        };
+
+        let resp = self.post(endpoint, &payload).await?;
+        if resp.status().is_success() {
+            let resp_body = resp.json::<GetWebhookDeliveriesResp>().await?;
+            Ok(resp_body.deliveries)
+        } else {
+            Err(webhook_error(&resp))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_only_serializes_set_fields() {
+        let update = WebhookUpdate {
+            is_active: Some(false),
+            ..WebhookUpdate::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::json!({ "isActive": false })
+        );
+    }
+
+    #[test]
+    fn update_debug_redacts_secret() {
+        let update = WebhookUpdate {
+            secret: Some(String::from("whsec_test")),
+            ..WebhookUpdate::default()
+        };
+
+        assert!(!format!("{update:?}").contains("whsec_test"));
+    }
+
+    #[test]
+    fn parses_unknown_event_types() {
+        let event_type = serde_json::from_str::<WebhookEventType>(r#""user.renamed""#).unwrap();
+
+        assert_eq!(event_type, WebhookEventType::Unknown);
+    }
+}
@@ -0,0 +1,774 @@
+use crate::{
+    error::HashGateError,
+    operations::{
+        delivery::DeliveryMethod, mfa::SignInOutcome, password_policy::PasswordPolicy, user::User,
+    },
+    provider::AuthProvider,
+    types::{
+        identity::{Email, Username},
+        responses::{
+            InitPasswordResetResp, InitVerificationResp, ResetPasswordResp, UpdateUserPasswordResp,
+            VerificationResp, VerifyPasswordResetResp,
+        },
+    },
+};
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+/// A code sent to a user by the fake, as HashGate would have delivered it.
+pub struct SentCode {
+    /// The uuid of the user the code was sent to.
+    pub user_id: Uuid,
+
+    /// The code that was sent.
+    pub code: String,
+}
+
+#[derive(Debug)]
+/// A user along with their salted password hash.
+struct FakeUser {
+    user: User,
+    salt: Uuid,
+    password_hash: Vec<u8>,
+    failed_sign_ins: u32,
+    mfa_code: Option<String>,
+}
+
+#[derive(Debug)]
+/// A session waiting on a code from the user.
+struct CodeSession {
+    user_id: Uuid,
+    code: String,
+    expires_at: NaiveDateTime,
+    attempts_remaining: u32,
+}
+
+#[derive(Debug)]
+struct FakeState {
+    now: NaiveDateTime,
+    token_ttl: Duration,
+    code_ttl: Duration,
+    max_attempts: u32,
+    lockout: Option<(u32, Duration)>,
+    password_policy: Option<PasswordPolicy>,
+    users: HashMap<Uuid, FakeUser>,
+    tokens: HashMap<String, (Uuid, NaiveDateTime)>,
+    mfa_challenges: HashMap<Uuid, (Uuid, NaiveDateTime)>,
+    verification_sessions: HashMap<Uuid, CodeSession>,
+    password_reset_verifications: HashMap<Uuid, CodeSession>,
+    password_reset_sessions: HashMap<Uuid, (Uuid, NaiveDateTime)>,
+    sent_codes: Vec<SentCode>,
+}
+impl FakeState {
+    /// Get the time a duration from now.
+    fn after(&self, duration: Duration) -> NaiveDateTime {
+        TimeDelta::from_std(duration)
+            .ok()
+            .and_then(|duration| self.now.checked_add_signed(duration))
+            .unwrap_or(NaiveDateTime::MAX)
+    }
+
+    /// Get a user, failing with the error HashGate gives for the operation if they don't exist.
+    fn user_mut(
+        &mut self,
+        user_id: &Uuid,
+        not_found: HashGateError,
+    ) -> Result<&mut FakeUser, HashGateError> {
+        self.users.get_mut(user_id).ok_or(not_found)
+    }
+
+    /// Sign a user in, returning their new auth token.
+    fn issue_token(&mut self, user_id: Uuid) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        let expires_at = self.after(self.token_ttl);
+        self.tokens.insert(token.clone(), (user_id, expires_at));
+        token
+    }
+
+    /// Check a password against the password policy, if one is set.
+    fn check_password(&self, password: &str) -> Result<(), HashGateError> {
+        match &self.password_policy {
+            Some(password_policy) => password_policy
+                .validate(password)
+                .map_err(HashGateError::WeakPassword),
+            None => Ok(()),
+        }
+    }
+
+    /// Start a code session for a user, returning the code if it should be returned.
+    fn issue_code(
+        &mut self,
+        user_id: Uuid,
+        delivery: &DeliveryMethod,
+    ) -> (CodeSession, Option<String>) {
+        let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+
+        let returned_code = match delivery {
+            DeliveryMethod::ReturnCode => Some(code.clone()),
+            DeliveryMethod::HashGate { .. } => {
+                self.sent_codes.push(SentCode {
+                    user_id,
+                    code: code.clone(),
+                });
+                None
+            }
+        };
+
+        let session = CodeSession {
+            user_id,
+            code,
+            expires_at: self.after(self.code_ttl),
+            attempts_remaining: self.max_attempts,
+        };
+
+        (session, returned_code)
+    }
+}
+
+/// Check a code against a session, consuming an attempt if it's wrong.
+///
+/// NOTE: HashGate rejects expired and exhausted sessions, which the client sees as a `ServerError`.
+fn check_code(
+    session: &mut CodeSession,
+    code: &str,
+    now: NaiveDateTime,
+) -> Result<bool, HashGateError> {
+    if session.expires_at <= now || session.attempts_remaining == 0 {
+        return Err(HashGateError::ServerError { request_id: None });
+    }
+
+    if session.code == code {
+        Ok(true)
+    } else {
+        session.attempts_remaining -= 1;
+        Ok(false)
+    }
+}
+
+/// Hash a password with a salt.
+fn hash_password(salt: &Uuid, password: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(password.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+#[derive(Debug, Clone)]
+/// An in memory HashGate backend for unit tests.
+///
+/// Usernames and emails are unique, passwords are stored salted and hashed,
+/// and codes, sessions and tokens expire against a clock the test controls.
+/// Codes delivered by "HashGate" can be read back with `FakeHashGate::sent_codes()`.
+///
+/// Failures come back as the same errors `HashGateClient` gives (ex: an expired
+/// token is `HashGateError::UserNotFound`), lockout and MFA can be turned on
+/// with `FakeHashGate::lockout()` and `FakeHashGate::enable_mfa()`.
+///
+/// NOTE: Clones share the same state.
+pub struct FakeHashGate {
+    state: Arc<Mutex<FakeState>>,
+}
+impl Default for FakeHashGate {
+    fn default() -> Self {
+        FakeHashGate::new()
+    }
+}
+impl FakeHashGate {
+    /// Create an empty `FakeHashGate`, with tokens living 4 hours,
+    /// codes living 10 minutes and 5 attempts per code.
+    pub fn new() -> FakeHashGate {
+        FakeHashGate {
+            state: Arc::new(Mutex::new(FakeState {
+                now: Utc::now().naive_utc(),
+                token_ttl: Duration::from_secs(4 * 60 * 60),
+                code_ttl: Duration::from_secs(10 * 60),
+                max_attempts: 5,
+                lockout: None,
+                password_policy: None,
+                users: HashMap::new(),
+                tokens: HashMap::new(),
+                mfa_challenges: HashMap::new(),
+                verification_sessions: HashMap::new(),
+                password_reset_verifications: HashMap::new(),
+                password_reset_sessions: HashMap::new(),
+                sent_codes: Vec::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        // A panicking test can poison the lock, the state is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set how long auth tokens live.
+    pub fn token_ttl(self, token_ttl: Duration) -> Self {
+        self.state().token_ttl = token_ttl;
+        self
+    }
+
+    /// Set how long verification and password reset codes live.
+    pub fn code_ttl(self, code_ttl: Duration) -> Self {
+        self.state().code_ttl = code_ttl;
+        self
+    }
+
+    /// Set how many attempts there are to enter a code.
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        self.state().max_attempts = max_attempts;
+        self
+    }
+
+    /// Lock users out for a duration after a number of failed sign ins in a row.
+    pub fn lockout(self, max_failures: u32, duration: Duration) -> Self {
+        self.state().lockout = Some((max_failures, duration));
+        self
+    }
+
+    /// Set the password policy new passwords are checked against.
+    pub fn password_policy(self, password_policy: PasswordPolicy) -> Self {
+        self.state().password_policy = Some(password_policy);
+        self
+    }
+
+    /// Get the current time of the fake clock.
+    pub fn now(&self) -> NaiveDateTime {
+        self.state().now
+    }
+
+    /// Set the fake clock.
+    pub fn set_now(&self, now: NaiveDateTime) {
+        self.state().now = now;
+    }
+
+    /// Move the fake clock forward (ex: past a token's expiry).
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state();
+        state.now = state.after(duration);
+    }
+
+    /// Get every code the fake delivered to users, oldest first.
+    pub fn sent_codes(&self) -> Vec<SentCode> {
+        self.state().sent_codes.clone()
+    }
+
+    /// Get the last code the fake delivered to a user.
+    pub fn last_code_for(&self, user_id: &Uuid) -> Option<String> {
+        self.state()
+            .sent_codes
+            .iter()
+            .rev()
+            .find(|sent_code| sent_code.user_id == *user_id)
+            .map(|sent_code| sent_code.code.clone())
+    }
+
+    /// Require an MFA code when a user signs in, the code stands in
+    /// for the one their authenticator app would show.
+    pub fn enable_mfa(&self, user_id: &Uuid, code: &str) -> Result<(), HashGateError> {
+        let mut state = self.state();
+
+        let fake_user = state.user_mut(user_id, HashGateError::ServerError { request_id: None })?;
+        fake_user.mfa_code = Some(code.to_owned());
+
+        Ok(())
+    }
+}
+
+impl AuthProvider for FakeHashGate {
+    async fn get_user(&mut self, user_id: &Uuid) -> Result<User, HashGateError> {
+        let mut state = self.state();
+        let not_found = HashGateError::UserNotFound { request_id: None };
+        Ok(state.user_mut(user_id, not_found)?.user.clone())
+    }
+
+    async fn get_user_by_token(&mut self, token: &str) -> Result<User, HashGateError> {
+        let mut state = self.state();
+
+        let (user_id, expires_at) = *state
+            .tokens
+            .get(token)
+            .ok_or(HashGateError::UserNotFound { request_id: None })?;
+        if expires_at <= state.now {
+            state.tokens.remove(token);
+            return Err(HashGateError::UserNotFound { request_id: None });
+        }
+
+        let not_found = HashGateError::UserNotFound { request_id: None };
+        Ok(state.user_mut(&user_id, not_found)?.user.clone())
+    }
+
+    async fn sign_in(
+        &mut self,
+        username: &str,
+        password: &str,
+    ) -> Result<SignInOutcome, HashGateError> {
+        let mut state = self.state();
+
+        let now = state.now;
+        let lockout = state
+            .lockout
+            .map(|(max_failures, duration)| (max_failures, state.after(duration)));
+        let failed = || HashGateError::FailedSignIn { request_id: None };
+
+        let username = Username::parse(username).map_err(|_| failed())?;
+        let fake_user = state
+            .users
+            .values_mut()
+            .find(|fake_user| fake_user.user.username == username.as_str())
+            .ok_or_else(failed)?;
+
+        if let Some(locked_until) = fake_user.user.locked_until.filter(|until| *until > now) {
+            return Err(HashGateError::AccountLocked {
+                until: Some(locked_until),
+            });
+        }
+        if fake_user.password_hash != hash_password(&fake_user.salt, password) {
+            fake_user.failed_sign_ins += 1;
+            if let Some((max_failures, locked_until)) = lockout {
+                if fake_user.failed_sign_ins >= max_failures {
+                    fake_user.failed_sign_ins = 0;
+                    fake_user.user.locked_until = Some(locked_until);
+                }
+            }
+            return Err(failed());
+        }
+
+        fake_user.failed_sign_ins = 0;
+        fake_user.user.locked_until = None;
+        let user_id = fake_user.user.id;
+
+        if fake_user.mfa_code.is_some() {
+            let challenge_id = Uuid::new_v4();
+            let expires_at = state.after(state.code_ttl);
+            state
+                .mfa_challenges
+                .insert(challenge_id, (user_id, expires_at));
+            return Ok(SignInOutcome::MfaRequired { challenge_id });
+        }
+
+        let token = state.issue_token(user_id);
+        Ok(SignInOutcome::Authenticated { token })
+    }
+
+    async fn complete_mfa_challenge(
+        &mut self,
+        challenge_id: &Uuid,
+        code: &str,
+    ) -> Result<String, HashGateError> {
+        let mut state = self.state();
+        let invalid = || HashGateError::InvalidMfaCode { request_id: None };
+
+        let (user_id, expires_at) = *state.mfa_challenges.get(challenge_id).ok_or_else(invalid)?;
+        if expires_at <= state.now {
+            state.mfa_challenges.remove(challenge_id);
+            return Err(invalid());
+        }
+
+        let mfa_code = state
+            .users
+            .get(&user_id)
+            .and_then(|fake_user| fake_user.mfa_code.as_deref());
+        if mfa_code != Some(code) {
+            return Err(invalid());
+        }
+
+        state.mfa_challenges.remove(challenge_id);
+        Ok(state.issue_token(user_id))
+    }
+
+    async fn create_user(
+        &mut self,
+        username: &str,
+        email: Option<&str>,
+        password: &str,
+    ) -> Result<User, HashGateError> {
+        let mut state = self.state();
+
+        let username = Username::parse(username)?;
+        let email = email.map(Email::parse).transpose()?;
+        state.check_password(password)?;
+
+        let is_taken = state.users.values().any(|fake_user| {
+            fake_user.user.username == username.as_str()
+                || email
+                    .as_ref()
+                    .is_some_and(|email| fake_user.user.email.as_deref() == Some(email.as_str()))
+        });
+        if is_taken {
+            return Err(HashGateError::UsernameTaken { request_id: None });
+        }
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.into(),
+            email: email.map(String::from),
+            phone_number: None,
+            is_verified: false,
+            group_id: None,
+            group_ids: Vec::new(),
+            permissions: Vec::new(),
+            locked_until: None,
+            creation_date: state.now,
+            updated_date: state.now,
+            custom_attributes: serde_json::json!({}),
+        };
+
+        let salt = Uuid::new_v4();
+        state.users.insert(
+            user.id,
+            FakeUser {
+                user: user.clone(),
+                salt,
+                password_hash: hash_password(&salt, password),
+                failed_sign_ins: 0,
+                mfa_code: None,
+            },
+        );
+
+        Ok(user)
+    }
+
+    async fn set_custom_attribute(
+        &mut self,
+        user_id: &Uuid,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), HashGateError> {
+        let mut state = self.state();
+        let now = state.now;
+
+        let not_found = HashGateError::CouldNotSetAttribute { request_id: None };
+        let user = &mut state.user_mut(user_id, not_found)?.user;
+        if !user.custom_attributes.is_object() {
+            user.custom_attributes = serde_json::json!({});
+        }
+        if let Some(attributes) = user.custom_attributes.as_object_mut() {
+            attributes.insert(key.to_owned(), value.clone());
+        }
+        user.updated_date = now;
+
+        Ok(())
+    }
+
+    async fn get_custom_attributes(
+        &mut self,
+        user_id: &Uuid,
+    ) -> Result<serde_json::Value, HashGateError> {
+        let mut state = self.state();
+        let not_found = HashGateError::UserNotFound { request_id: None };
+        Ok(state
+            .user_mut(user_id, not_found)?
+            .user
+            .custom_attributes
+            .clone())
+    }
+
+    async fn update_password(
+        &mut self,
+        user_id: &Uuid,
+        new_password: &str,
+    ) -> Result<UpdateUserPasswordResp, HashGateError> {
+        let mut state = self.state();
+        let now = state.now;
+
+        state.check_password(new_password)?;
+
+        let fake_user = state.user_mut(user_id, HashGateError::ServerError { request_id: None })?;
+        fake_user.password_hash = hash_password(&fake_user.salt, new_password);
+        fake_user.user.updated_date = now;
+
+        Ok(UpdateUserPasswordResp {
+            message: String::from("Password Updated"),
+            was_successful: true,
+        })
+    }
+
+    async fn init_verification(
+        &mut self,
+        user_id: &Uuid,
+        delivery: &DeliveryMethod,
+    ) -> Result<InitVerificationResp, HashGateError> {
+        let mut state = self.state();
+
+        state.user_mut(user_id, HashGateError::ServerError { request_id: None })?;
+        let (session, verification_code) = state.issue_code(*user_id, delivery);
+
+        let verification_session_id = Uuid::new_v4();
+        state
+            .verification_sessions
+            .insert(verification_session_id, session);
+
+        Ok(InitVerificationResp {
+            verification_session_id,
+            verification_code,
+            was_successful: true,
+        })
+    }
+
+    async fn complete_verification(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> Result<VerificationResp, HashGateError> {
+        let mut state = self.state();
+        let now = state.now;
+
+        let session = state
+            .verification_sessions
+            .get_mut(verification_session_id)
+            .ok_or(HashGateError::ServerError { request_id: None })?;
+        if !check_code(session, code, now)? {
+            return Ok(VerificationResp {
+                is_verified: false,
+                user_id: Some(session.user_id),
+                message: String::from("Invalid Verification Code"),
+                was_successful: false,
+            });
+        }
+
+        let user_id = session.user_id;
+        state.verification_sessions.remove(verification_session_id);
+
+        let user = &mut state
+            .user_mut(&user_id, HashGateError::ServerError { request_id: None })?
+            .user;
+        user.is_verified = true;
+        user.updated_date = now;
+
+        Ok(VerificationResp {
+            is_verified: true,
+            user_id: Some(user_id),
+            message: String::from("User Verified"),
+            was_successful: true,
+        })
+    }
+
+    async fn start_password_reset(
+        &mut self,
+        email: &str,
+        delivery: &DeliveryMethod,
+    ) -> Result<InitPasswordResetResp, HashGateError> {
+        let mut state = self.state();
+
+        let email = Email::parse(email)?;
+        let user_id = state
+            .users
+            .values()
+            .find(|fake_user| fake_user.user.email.as_deref() == Some(email.as_str()))
+            .map(|fake_user| fake_user.user.id)
+            .ok_or(HashGateError::ServerError { request_id: None })?;
+
+        let (session, verification_code) = state.issue_code(user_id, delivery);
+        let expires_at = session.expires_at;
+        let max_attempts = session.attempts_remaining;
+
+        let verification_session_id = Uuid::new_v4();
+        state
+            .password_reset_verifications
+            .insert(verification_session_id, session);
+
+        Ok(InitPasswordResetResp {
+            verification_session_id,
+            verification_code,
+            expires_at: Some(expires_at),
+            max_attempts: Some(max_attempts),
+            was_successful: true,
+        })
+    }
+
+    async fn confirm_password_reset(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> Result<VerifyPasswordResetResp, HashGateError> {
+        let mut state = self.state();
+        let now = state.now;
+
+        let session = state
+            .password_reset_verifications
+            .get_mut(verification_session_id)
+            .ok_or(HashGateError::ServerError { request_id: None })?;
+        if !check_code(session, code, now)? {
+            return Ok(VerifyPasswordResetResp {
+                password_reset_session_id: None,
+                message: String::from("Invalid Verification Code"),
+                attempts_remaining: Some(session.attempts_remaining),
+                expires_at: Some(session.expires_at),
+                was_successful: false,
+            });
+        }
+
+        let user_id = session.user_id;
+        state
+            .password_reset_verifications
+            .remove(verification_session_id);
+
+        let password_reset_session_id = Uuid::new_v4();
+        let expires_at = state.after(state.code_ttl);
+        state
+            .password_reset_sessions
+            .insert(password_reset_session_id, (user_id, expires_at));
+
+        Ok(VerifyPasswordResetResp {
+            password_reset_session_id: Some(password_reset_session_id),
+            message: String::from("Password Reset Verified"),
+            attempts_remaining: None,
+            expires_at: Some(expires_at),
+            was_successful: true,
+        })
+    }
+
+    async fn reset_password(
+        &mut self,
+        password_reset_session_id: &Uuid,
+        new_password: &str,
+    ) -> Result<ResetPasswordResp, HashGateError> {
+        let mut state = self.state();
+        let now = state.now;
+
+        let (user_id, expires_at) = *state
+            .password_reset_sessions
+            .get(password_reset_session_id)
+            .ok_or(HashGateError::ServerError { request_id: None })?;
+        if expires_at <= now {
+            state
+                .password_reset_sessions
+                .remove(password_reset_session_id);
+            return Err(HashGateError::ServerError { request_id: None });
+        }
+        state.check_password(new_password)?;
+
+        state
+            .password_reset_sessions
+            .remove(password_reset_session_id);
+
+        let fake_user =
+            state.user_mut(&user_id, HashGateError::ServerError { request_id: None })?;
+        fake_user.password_hash = hash_password(&fake_user.salt, new_password);
+        fake_user.user.updated_date = now;
+
+        // Resetting a password signs the user out everywhere
+        state
+            .tokens
+            .retain(|_, (token_user_id, _)| *token_user_id != user_id);
+
+        Ok(ResetPasswordResp {
+            message: String::from("Password Reset"),
+            was_successful: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    fn fake_with_user() -> (FakeHashGate, User) {
+        let mut fake = FakeHashGate::new().lockout(3, Duration::from_secs(60));
+        let user = fake
+            .create_user("alice", None, "hunter22")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+
+        (fake, user)
+    }
+
+    fn sign_in(fake: &mut FakeHashGate, password: &str) -> Result<SignInOutcome, HashGateError> {
+        fake.sign_in("alice", password).now_or_never().unwrap()
+    }
+
+    #[test]
+    fn expired_tokens_are_user_not_found() {
+        let (mut fake, user) = fake_with_user();
+
+        let Ok(SignInOutcome::Authenticated { token }) = sign_in(&mut fake, "hunter22") else {
+            panic!("sign in failed");
+        };
+        let found = fake.get_user_by_token(&token).now_or_never().unwrap();
+        assert_eq!(found.unwrap().id, user.id);
+
+        fake.advance(Duration::from_secs(4 * 60 * 60));
+        assert!(matches!(
+            fake.get_user_by_token(&token).now_or_never().unwrap(),
+            Err(HashGateError::UserNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn locks_out_after_failed_sign_ins() {
+        let (mut fake, _) = fake_with_user();
+
+        for _ in 0..3 {
+            assert!(matches!(
+                sign_in(&mut fake, "wrong"),
+                Err(HashGateError::FailedSignIn { .. })
+            ));
+        }
+        assert!(matches!(
+            sign_in(&mut fake, "hunter22"),
+            Err(HashGateError::AccountLocked { until: Some(_) })
+        ));
+
+        fake.advance(Duration::from_secs(60));
+        assert!(sign_in(&mut fake, "hunter22").is_ok());
+    }
+
+    #[test]
+    fn requires_mfa_when_enabled() {
+        let (mut fake, user) = fake_with_user();
+        fake.enable_mfa(&user.id, "123456").unwrap();
+
+        let Ok(SignInOutcome::MfaRequired { challenge_id }) = sign_in(&mut fake, "hunter22") else {
+            panic!("mfa wasn't required");
+        };
+        assert!(matches!(
+            fake.complete_mfa_challenge(&challenge_id, "000000")
+                .now_or_never()
+                .unwrap(),
+            Err(HashGateError::InvalidMfaCode { .. })
+        ));
+
+        let token = fake
+            .complete_mfa_challenge(&challenge_id, "123456")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let found = fake.get_user_by_token(&token).now_or_never().unwrap();
+        assert_eq!(found.unwrap().id, user.id);
+    }
+
+    #[test]
+    fn exhausted_codes_are_rejected_like_hashgate() {
+        let (fake, user) = fake_with_user();
+        let mut fake = fake.max_attempts(1);
+
+        let session = fake
+            .init_verification(&user.id, &DeliveryMethod::ReturnCode)
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        let session_id = session.verification_session_id;
+
+        let resp = fake
+            .complete_verification(&session_id, "wrong")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert!(!resp.was_successful);
+        assert!(matches!(
+            fake.complete_verification(&session_id, "wrong")
+                .now_or_never()
+                .unwrap(),
+            Err(HashGateError::ServerError { .. })
+        ));
+    }
+}
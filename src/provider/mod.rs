@@ -0,0 +1,226 @@
+use crate::{
+    client::HashGateClient,
+    error::HashGateError,
+    operations::{delivery::DeliveryMethod, mfa::SignInOutcome, user::User},
+    types::responses::{
+        InitPasswordResetResp, InitVerificationResp, ResetPasswordResp, UpdateUserPasswordResp,
+        VerificationResp, VerifyPasswordResetResp,
+    },
+};
+use std::future::Future;
+use uuid::Uuid;
+
+#[cfg(feature = "fake")]
+pub mod fake;
+
+/// The user operations of a HashGate backend, so code can depend on the
+/// operations instead of `HashGateClient` itself (ex: to swap in a fake in tests).
+///
+/// NOTE: Methods are named so they don't shadow the inherent `HashGateClient`
+/// methods they call (ex: `start_password_reset` calls `init_password_reset`),
+/// unless they take the same arguments (ex: `complete_mfa_challenge`).
+///
+/// With the `mock` feature a `mockall` mock is generated as `MockAuthProvider`.
+#[cfg_attr(feature = "mock", mockall::automock)]
+pub trait AuthProvider {
+    /// Get a user by id.
+    fn get_user(
+        &mut self,
+        user_id: &Uuid,
+    ) -> impl Future<Output = Result<User, HashGateError>> + Send;
+
+    /// Get the user an auth token belongs to.
+    fn get_user_by_token(
+        &mut self,
+        token: &str,
+    ) -> impl Future<Output = Result<User, HashGateError>> + Send;
+
+    /// Sign a user in with their username and password.
+    fn sign_in(
+        &mut self,
+        username: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<SignInOutcome, HashGateError>> + Send;
+
+    /// Complete the MFA challenge of a sign in with a TOTP code, returning the auth token.
+    fn complete_mfa_challenge(
+        &mut self,
+        challenge_id: &Uuid,
+        code: &str,
+    ) -> impl Future<Output = Result<String, HashGateError>> + Send;
+
+    /// Register a user.
+    // NOTE: The named lifetime is needed for `mockall::automock`
+    #[allow(clippy::needless_lifetimes)]
+    fn create_user<'a>(
+        &mut self,
+        username: &str,
+        email: Option<&'a str>,
+        password: &str,
+    ) -> impl Future<Output = Result<User, HashGateError>> + Send;
+
+    /// Set a custom attribute for a user.
+    fn set_custom_attribute(
+        &mut self,
+        user_id: &Uuid,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> impl Future<Output = Result<(), HashGateError>> + Send;
+
+    /// Get all custom attributes for a user.
+    fn get_custom_attributes(
+        &mut self,
+        user_id: &Uuid,
+    ) -> impl Future<Output = Result<serde_json::Value, HashGateError>> + Send;
+
+    /// Update a user's password.
+    fn update_password(
+        &mut self,
+        user_id: &Uuid,
+        new_password: &str,
+    ) -> impl Future<Output = Result<UpdateUserPasswordResp, HashGateError>> + Send;
+
+    /// Initialize a verification session for a user.
+    fn init_verification(
+        &mut self,
+        user_id: &Uuid,
+        delivery: &DeliveryMethod,
+    ) -> impl Future<Output = Result<InitVerificationResp, HashGateError>> + Send;
+
+    /// Complete a verification session with the code the user gives to you.
+    fn complete_verification(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> impl Future<Output = Result<VerificationResp, HashGateError>> + Send;
+
+    /// Initialize a password reset for the user with an email.
+    fn start_password_reset(
+        &mut self,
+        email: &str,
+        delivery: &DeliveryMethod,
+    ) -> impl Future<Output = Result<InitPasswordResetResp, HashGateError>> + Send;
+
+    /// Verify a password reset with the code the user gives to you.
+    fn confirm_password_reset(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> impl Future<Output = Result<VerifyPasswordResetResp, HashGateError>> + Send;
+
+    /// Reset a user's password, completing a verified password reset.
+    fn reset_password(
+        &mut self,
+        password_reset_session_id: &Uuid,
+        new_password: &str,
+    ) -> impl Future<Output = Result<ResetPasswordResp, HashGateError>> + Send;
+}
+
+/// The user operations of HashGate itself.
+impl AuthProvider for HashGateClient {
+    async fn get_user(&mut self, user_id: &Uuid) -> Result<User, HashGateError> {
+        User::try_from_id(*user_id, self).await
+    }
+
+    async fn get_user_by_token(&mut self, token: &str) -> Result<User, HashGateError> {
+        User::try_from_token(token, self).await
+    }
+
+    async fn sign_in(
+        &mut self,
+        username: &str,
+        password: &str,
+    ) -> Result<SignInOutcome, HashGateError> {
+        self.authenticate_user(username.to_owned(), password.to_owned())
+            .await
+    }
+
+    async fn complete_mfa_challenge(
+        &mut self,
+        challenge_id: &Uuid,
+        code: &str,
+    ) -> Result<String, HashGateError> {
+        HashGateClient::complete_mfa_challenge(self, challenge_id, code).await
+    }
+
+    async fn create_user(
+        &mut self,
+        username: &str,
+        email: Option<&str>,
+        password: &str,
+    ) -> Result<User, HashGateError> {
+        let mut registration = self.register(username, password);
+        if let Some(email) = email {
+            registration = registration.email(email);
+        }
+
+        registration.send().await
+    }
+
+    async fn set_custom_attribute(
+        &mut self,
+        user_id: &Uuid,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), HashGateError> {
+        self.set_user_attribute(user_id, key, value, None).await
+    }
+
+    async fn get_custom_attributes(
+        &mut self,
+        user_id: &Uuid,
+    ) -> Result<serde_json::Value, HashGateError> {
+        self.get_user_attributes(user_id).await
+    }
+
+    async fn update_password(
+        &mut self,
+        user_id: &Uuid,
+        new_password: &str,
+    ) -> Result<UpdateUserPasswordResp, HashGateError> {
+        self.update_user_password(user_id, new_password.to_owned(), None)
+            .await
+    }
+
+    async fn init_verification(
+        &mut self,
+        user_id: &Uuid,
+        delivery: &DeliveryMethod,
+    ) -> Result<InitVerificationResp, HashGateError> {
+        self.init_user_verification(user_id, delivery).await
+    }
+
+    async fn complete_verification(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> Result<VerificationResp, HashGateError> {
+        self.verify(*verification_session_id, code).await
+    }
+
+    async fn start_password_reset(
+        &mut self,
+        email: &str,
+        delivery: &DeliveryMethod,
+    ) -> Result<InitPasswordResetResp, HashGateError> {
+        self.init_password_reset(email, delivery).await
+    }
+
+    async fn confirm_password_reset(
+        &mut self,
+        verification_session_id: &Uuid,
+        code: &str,
+    ) -> Result<VerifyPasswordResetResp, HashGateError> {
+        self.verify_password_reset(verification_session_id, code)
+            .await
+    }
+
+    async fn reset_password(
+        &mut self,
+        password_reset_session_id: &Uuid,
+        new_password: &str,
+    ) -> Result<ResetPasswordResp, HashGateError> {
+        self.reset_user_password(password_reset_session_id, new_password)
+            .await
+    }
+}
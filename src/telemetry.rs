@@ -0,0 +1,71 @@
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Counter of requests sent to HashGate,
+/// labeled by `endpoint`, `method` and `status` (ex: `2xx`, or `error` if no response).
+///
+/// NOTE: Requests sent with `HashGateClient::send_request()` are labeled with the
+/// `custom` endpoint and `CUSTOM` method, and a re-auth retry counts as one request.
+pub const REQUESTS_TOTAL: &str = "hashgate_requests_total";
+
+/// Histogram of request latency in seconds, labeled by `endpoint` and `method`.
+pub const REQUEST_DURATION_SECONDS: &str = "hashgate_request_duration_seconds";
+
+/// Counter of requests retried after re-authing the client, labeled by `endpoint`.
+///
+/// NOTE: This is the only retry the client makes, so it's at most one per request.
+pub const RETRIES_TOTAL: &str = "hashgate_retries_total";
+
+/// Counter of client re-auths (expired client tokens), labeled by `endpoint`.
+pub const REAUTHS_TOTAL: &str = "hashgate_reauths_total";
+
+/// Counter of cache lookups, labeled by `cache` and `result` (`hit` or `miss`).
+pub const CACHE_LOOKUPS_TOTAL: &str = "hashgate_cache_lookups_total";
+
+/// Get the status class label for a response (ex: `4xx`).
+fn status_class(status: Option<StatusCode>) -> &'static str {
+    match status.map(|status| status.as_u16() / 100) {
+        Some(1) => "1xx",
+        Some(2) => "2xx",
+        Some(3) => "3xx",
+        Some(4) => "4xx",
+        Some(5) => "5xx",
+        _ => "error",
+    }
+}
+
+/// Record a request, `status` is `None` when no response came back.
+pub(crate) fn record_request(
+    method: &'static str,
+    endpoint: &str,
+    status: Option<StatusCode>,
+    elapsed: Duration,
+) {
+    metrics::counter!(
+        REQUESTS_TOTAL,
+        "endpoint" => endpoint.to_owned(),
+        "method" => method,
+        "status" => status_class(status),
+    )
+    .increment(1);
+    metrics::histogram!(
+        REQUEST_DURATION_SECONDS,
+        "endpoint" => endpoint.to_owned(),
+        "method" => method,
+    )
+    .record(elapsed.as_secs_f64());
+}
+
+/// Record the client re-authing, and whether the request was retried.
+pub(crate) fn record_reauth(endpoint: &str, is_retried: bool) {
+    metrics::counter!(REAUTHS_TOTAL, "endpoint" => endpoint.to_owned()).increment(1);
+    if is_retried {
+        metrics::counter!(RETRIES_TOTAL, "endpoint" => endpoint.to_owned()).increment(1);
+    }
+}
+
+/// Record a cache lookup.
+pub(crate) fn record_cache_lookup(cache: &'static str, is_hit: bool) {
+    let result = if is_hit { "hit" } else { "miss" };
+    metrics::counter!(CACHE_LOOKUPS_TOTAL, "cache" => cache, "result" => result).increment(1);
+}
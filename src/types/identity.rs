@@ -0,0 +1,171 @@
+use crate::error::HashGateError;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalize user input with NFKC and lowercasing, so visually
+/// identical strings (ex: full width letters) compare equal.
+fn normalize(value: &str) -> String {
+    value.trim().nfkc().collect::<String>().to_lowercase()
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+/// A validated and normalized username.
+///
+/// Usernames are NFKC normalized and lowercased, must be 3 to 32
+/// characters long, start with a letter or digit, and only contain
+/// ASCII letters, digits, `_`, `-` and `.` (ruling out confusables).
+pub struct Username(String);
+impl Username {
+    /// The minimum number of characters in a username.
+    pub const MIN_LENGTH: usize = 3;
+
+    /// The maximum number of characters in a username.
+    pub const MAX_LENGTH: usize = 32;
+
+    /// Try to parse a `Username`.
+    pub fn parse(value: &str) -> Result<Username, HashGateError> {
+        let username = normalize(value);
+        let invalid = |reason: &str| Err(HashGateError::InvalidUsername(reason.to_owned()));
+
+        if username.len() < Self::MIN_LENGTH || username.len() > Self::MAX_LENGTH {
+            return invalid("Must Be Between 3 And 32 Characters");
+        }
+        if !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return invalid("Can Only Contain Letters, Digits, '_', '-' And '.'");
+        }
+        if !username.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            return invalid("Must Start With A Letter Or Digit");
+        }
+
+        Ok(Username(username))
+    }
+
+    /// Get the username as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+/// A validated and normalized email address.
+///
+/// Emails are NFKC normalized and lowercased, the local part must be a
+/// RFC 5322 dot-atom, and the domain is converted to its ASCII (IDNA) form.
+pub struct Email(String);
+impl Email {
+    /// The maximum number of characters in an email address.
+    pub const MAX_LENGTH: usize = 254;
+
+    /// The maximum number of characters in the local part of an email address.
+    pub const MAX_LOCAL_LENGTH: usize = 64;
+
+    /// Try to parse an `Email`.
+    pub fn parse(value: &str) -> Result<Email, HashGateError> {
+        let email = normalize(value);
+        let invalid = |reason: &str| Err(HashGateError::InvalidEmail(reason.to_owned()));
+
+        let Some((local, domain)) = email.rsplit_once('@') else {
+            return invalid("Missing '@'");
+        };
+
+        if local.is_empty() || local.len() > Self::MAX_LOCAL_LENGTH {
+            return invalid("Local Part Must Be Between 1 And 64 Characters");
+        }
+        if !is_dot_atom(local) {
+            return invalid("Local Part Contains Invalid Characters");
+        }
+
+        let Ok(domain) = idna::domain_to_ascii(domain) else {
+            return invalid("Invalid Domain");
+        };
+        if !is_valid_domain(&domain) {
+            return invalid("Invalid Domain");
+        }
+
+        let email = format!("{local}@{domain}");
+        if email.len() > Self::MAX_LENGTH {
+            return invalid("Must Be At Most 254 Characters");
+        }
+
+        Ok(Email(email))
+    }
+
+    /// Get the email as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the domain of the email.
+    pub fn domain(&self) -> &str {
+        self.0
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or_default()
+    }
+}
+
+/// Check if a string is a RFC 5322 dot-atom (ex: `first.last+tag`).
+fn is_dot_atom(value: &str) -> bool {
+    let is_atext = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c);
+
+    value
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// Check if an ASCII domain has at least two valid labels.
+fn is_valid_domain(domain: &str) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+
+    labels.len() >= 2
+        && domain.len() <= 253
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+macro_rules! impl_string_newtype {
+    ($name:ident) => {
+        impl FromStr for $name {
+            type Err = HashGateError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                $name::parse(value)
+            }
+        }
+        impl TryFrom<String> for $name {
+            type Error = HashGateError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::parse(&value)
+            }
+        }
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+impl_string_newtype!(Username);
+impl_string_newtype!(Email);
@@ -1,3 +1,11 @@
+use crate::{
+    operations::{
+        delivery::{DeliveryChannel, DeliveryMethod},
+        events::EventFilter,
+        webhook::{WebhookEventType, WebhookUpdate},
+    },
+    types::identity::{Email, Username},
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -13,10 +21,13 @@ pub struct ClientAuthReq {
 #[serde(rename_all = "camelCase")]
 /// HashGate User Registration Request
 pub struct UserRegistrationReq {
-    pub username: String,
-    pub email: Option<String>,
+    pub username: Username,
+    pub email: Option<Email>,
     pub password: String,
     pub group_id: Option<i32>,
+    pub group_ids: Vec<i32>,
+    pub custom_attributes: Option<serde_json::Value>,
+    pub send_verification: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -70,6 +81,8 @@ pub struct GetUserCustomAttributeReq {
 /// HashGate Initialize Verification Session Request
 pub struct InitVerificationReq {
     pub user_id: Uuid,
+    #[serde(flatten)]
+    pub delivery: CodeDeliveryReq,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -92,7 +105,9 @@ pub struct UpdateUserPasswordReq {
 #[serde(rename_all = "camelCase")]
 /// HashGate initialize password reset Request
 pub struct InitPasswordResetReq {
-    pub email: String,
+    pub email: Email,
+    #[serde(flatten)]
+    pub delivery: CodeDeliveryReq,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -110,3 +125,154 @@ pub struct ResetPasswordReq {
     pub password_reset_session_id: Uuid,
     pub new_password: String,
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Enroll `User` In TOTP Request
+pub struct EnrollTotpReq {
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Confirm `User`s TOTP Enrollment Request
+pub struct ConfirmTotpReq {
+    pub user_id: Uuid,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Generate `User` Recovery Codes Request
+pub struct GenerateRecoveryCodesReq {
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Complete MFA Challenge Request
+pub struct CompleteMfaChallengeReq {
+    pub challenge_id: Uuid,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Initialize Passwordless Sign In Request
+pub struct InitPasswordlessSignInReq {
+    pub email: Email,
+    pub code_length: u8,
+    pub ttl_seconds: u32,
+    pub max_attempts: u32,
+    pub magic_link_base_url: Option<String>,
+    #[serde(flatten)]
+    pub delivery: CodeDeliveryReq,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Complete Passwordless Sign In Request
+pub struct CompletePasswordlessSignInReq {
+    pub verification_session_id: Uuid,
+    pub verification_code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Code Delivery Options, flattened into requests that issue codes
+pub struct CodeDeliveryReq {
+    pub delivery_channel: Option<DeliveryChannel>,
+    pub template_id: Option<String>,
+    pub return_code: bool,
+}
+impl From<&DeliveryMethod> for CodeDeliveryReq {
+    fn from(delivery: &DeliveryMethod) -> Self {
+        match delivery {
+            DeliveryMethod::HashGate {
+                channel,
+                template_id,
+            } => CodeDeliveryReq {
+                delivery_channel: Some(*channel),
+                template_id: template_id.clone(),
+                return_code: false,
+            },
+            DeliveryMethod::ReturnCode => CodeDeliveryReq {
+                delivery_channel: None,
+                template_id: None,
+                return_code: true,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Create Group Request
+pub struct CreateGroupReq {
+    pub group_name: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Add/Remove `User` To/From Group Request
+pub struct GroupMembershipReq {
+    pub user_id: Uuid,
+    pub group_id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Get a `User`s Groups Request
+pub struct GetUserGroupsReq {
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Unlock `User` Request
+pub struct UnlockUserReq {
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Get Pool Events Request
+pub struct GetEventsReq {
+    #[serde(flatten)]
+    pub filter: EventFilter,
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Create Webhook Request
+pub struct CreateWebhookReq {
+    pub url: String,
+    pub events: Vec<WebhookEventType>,
+    pub secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Update Webhook Request
+pub struct UpdateWebhookReq {
+    pub webhook_id: Uuid,
+    #[serde(flatten)]
+    pub update: WebhookUpdate,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Delete Webhook / Get Webhook Deliveries Request
+pub struct WebhookIdReq {
+    pub webhook_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// HashGate Test Webhook Request
+pub struct TestWebhookReq {
+    pub webhook_id: Uuid,
+    pub event_type: WebhookEventType,
+}
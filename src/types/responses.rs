@@ -1,4 +1,12 @@
-use crate::operations::user::User;
+use crate::operations::{
+    events::UserEvent,
+    group::Group,
+    password_policy::PasswordPolicy,
+    pool::Pool,
+    user::User,
+    webhook::{Webhook, WebhookDelivery},
+};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -7,6 +15,8 @@ use uuid::Uuid;
 pub struct AuthResponse {
     pub token: Option<String>,
     pub message: Option<String>,
+    pub mfa_challenge_id: Option<Uuid>,
+    pub locked_until: Option<NaiveDateTime>,
     pub was_successful: bool,
 }
 
@@ -57,7 +67,9 @@ pub struct UpdateUserPasswordResp {
 #[serde(rename_all = "camelCase")]
 pub struct InitPasswordResetResp {
     pub verification_session_id: Uuid,
-    pub verification_code: String,
+    pub verification_code: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub max_attempts: Option<u32>,
     pub was_successful: bool,
 }
 
@@ -65,7 +77,7 @@ pub struct InitPasswordResetResp {
 #[serde(rename_all = "camelCase")]
 pub struct InitVerificationResp {
     pub verification_session_id: Uuid,
-    pub verification_code: String,
+    pub verification_code: Option<String>,
     pub was_successful: bool,
 }
 
@@ -83,6 +95,8 @@ pub struct VerificationResp {
 pub struct VerifyPasswordResetResp {
     pub password_reset_session_id: Option<Uuid>,
     pub message: String,
+    pub attempts_remaining: Option<u32>,
+    pub expires_at: Option<NaiveDateTime>,
     pub was_successful: bool,
 }
 
@@ -92,3 +106,120 @@ pub struct ResetPasswordResp {
     pub message: String,
     pub was_successful: bool,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollTotpResp {
+    pub secret: String,
+    pub provisioning_uri: String,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmTotpResp {
+    pub message: String,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateRecoveryCodesResp {
+    pub recovery_codes: Vec<String>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InitPasswordlessSignInResp {
+    pub verification_session_id: Uuid,
+    pub verification_code: Option<String>,
+    pub magic_link: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGroupResp {
+    pub group: Option<Group>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGroupsResp {
+    pub groups: Vec<Group>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMembershipResp {
+    pub message: String,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPasswordPolicyResp {
+    pub password_policy: PasswordPolicy,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockUserResp {
+    pub message: String,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsResp {
+    pub events: Vec<UserEvent>,
+    pub next_cursor: Option<String>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookResp {
+    pub webhook: Option<Webhook>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWebhooksResp {
+    pub webhooks: Vec<Webhook>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteWebhookResp {
+    pub message: String,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TestWebhookResp {
+    pub delivery: Option<WebhookDelivery>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWebhookDeliveriesResp {
+    pub deliveries: Vec<WebhookDelivery>,
+    pub was_successful: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPoolResp {
+    pub pool: Option<Pool>,
+    pub was_successful: bool,
+}
@@ -0,0 +1,62 @@
+use crate::{
+    error::HashGateError,
+    webhooks::{WebhookEvent, WebhookVerifier},
+};
+use axum::{
+    body::to_bytes,
+    extract::{FromRef, FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// The largest webhook body accepted, in bytes.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone)]
+/// Extractor for a verified HashGate webhook event, rejecting with a 400
+/// if the body can't be read or isn't an event, and a 401 if the delivery
+/// fails verification (ex: a bad signature, or a replay).
+///
+/// NOTE: The `WebhookVerifier` must be available from your router state:
+///
+/// ```ignore
+/// async fn on_webhook(VerifiedWebhook(event): VerifiedWebhook) -> StatusCode {
+///     match event.data {
+///         WebhookEventData::UserDeleted { user_id } => { /* clean up */ }
+///         _ => {}
+///     }
+///     StatusCode::NO_CONTENT
+/// }
+///
+/// let app = Router::new()
+///     .route("/webhooks/hashgate", post(on_webhook))
+///     .with_state(WebhookVerifier::new(&secret));
+/// ```
+pub struct VerifiedWebhook(pub WebhookEvent);
+impl<S> FromRequest<S> for VerifiedWebhook
+where
+    WebhookVerifier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let verifier = WebhookVerifier::from_ref(state);
+        let (parts, body) = req.into_parts();
+
+        let body = to_bytes(body, MAX_BODY_SIZE)
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid Webhook Body").into_response())?;
+
+        verifier
+            .verify_and_parse(&parts.headers, &body)
+            .map(VerifiedWebhook)
+            .map_err(|e| {
+                let status = match e {
+                    HashGateError::MalformedWebhook(_) => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::UNAUTHORIZED,
+                };
+                (status, e.to_string()).into_response()
+            })
+    }
+}
@@ -0,0 +1,398 @@
+pub use crate::operations::webhook::WebhookEventType;
+use crate::{error::HashGateError, operations::user::User};
+use chrono::{NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha256;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use uuid::Uuid;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// The header HashGate signs webhook deliveries with,
+/// formatted as `t=<unix timestamp>,v1=<hex signature>`.
+pub const SIGNATURE_HEADER: &str = "x-hashgate-signature";
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(tag = "type", content = "data", remote = "Self")]
+/// The payload of a webhook event.
+pub enum WebhookEventData {
+    /// A user was created.
+    #[serde(rename = "user.created")]
+    UserCreated { user: User },
+
+    /// A user was verified.
+    #[serde(rename = "user.verified")]
+    UserVerified { user: User },
+
+    /// A user reset their password.
+    #[serde(rename = "user.password_reset", rename_all = "camelCase")]
+    PasswordReset { user_id: Uuid },
+
+    /// A user was deleted.
+    #[serde(rename = "user.deleted", rename_all = "camelCase")]
+    UserDeleted { user_id: Uuid },
+
+    /// An event type this version doesn't know about, its data is ignored.
+    #[serde(skip_deserializing)]
+    Unknown,
+}
+impl Serialize for WebhookEventData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WebhookEventData::serialize(self, serializer)
+    }
+}
+impl<'de> Deserialize<'de> for WebhookEventData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Tag {
+            #[serde(rename = "type")]
+            event_type: WebhookEventType,
+        }
+
+        // `#[serde(other)]` can't skip the data of an adjacently tagged variant,
+        // so unknown types are caught on the tag before parsing the data
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let tag = Tag::deserialize(&value).map_err(D::Error::custom)?;
+        if tag.event_type == WebhookEventType::Unknown {
+            return Ok(WebhookEventData::Unknown);
+        }
+
+        WebhookEventData::deserialize(value).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// A webhook event sent by HashGate.
+pub struct WebhookEvent {
+    /// The uuid of the event, the same across delivery retries.
+    pub event_id: Uuid,
+
+    /// When the event happened.
+    pub created_at: NaiveDateTime,
+
+    /// The payload of the event.
+    #[serde(flatten)]
+    pub data: WebhookEventData,
+}
+impl WebhookEvent {
+    /// Get the type of the event.
+    pub fn event_type(&self) -> WebhookEventType {
+        match self.data {
+            WebhookEventData::UserCreated { .. } => WebhookEventType::UserCreated,
+            WebhookEventData::UserVerified { .. } => WebhookEventType::UserVerified,
+            WebhookEventData::PasswordReset { .. } => WebhookEventType::PasswordReset,
+            WebhookEventData::UserDeleted { .. } => WebhookEventType::UserDeleted,
+            WebhookEventData::Unknown => WebhookEventType::Unknown,
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Verifies webhook deliveries from HashGate against your webhook secret.
+///
+/// Deliveries are rejected if the signature doesn't match, the timestamp is
+/// outside the tolerance, or the delivery was already seen (replay protection).
+/// Clones share the replay protection state.
+///
+/// NOTE: Deliveries are keyed on the event and its signed timestamp, retries of
+/// an event are signed again so they're still accepted if your handler failed.
+/// Deduplicate on `WebhookEvent::event_id` if your handler isn't idempotent.
+pub struct WebhookVerifier {
+    secret: Vec<u8>,
+    tolerance: Duration,
+    seen_deliveries: Arc<Mutex<HashSet<(Uuid, i64)>>>,
+}
+impl std::fmt::Debug for WebhookVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("WebhookVerifier")
+            .field("secret", &"[REDACTED]")
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
+    }
+}
+impl WebhookVerifier {
+    /// Create a `WebhookVerifier` for a webhook secret, with a 5 minute tolerance.
+    pub fn new(secret: &str) -> WebhookVerifier {
+        WebhookVerifier {
+            secret: secret.as_bytes().to_vec(),
+            tolerance: Duration::from_secs(5 * 60),
+            seen_deliveries: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Set how far a delivery's timestamp can be from now.
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Verify a webhook delivery and parse its event.
+    ///
+    /// The headers can come from any framework, as long as they iterate
+    /// as (name, value) pairs (ex: `&http::HeaderMap` or actix's `HeaderMap`).
+    ///
+    /// NOTE: A delivery that fails verification is `HashGateError::InvalidWebhook`,
+    /// a verified delivery whose body isn't an event is `HashGateError::MalformedWebhook`.
+    pub fn verify_and_parse<K, V>(
+        &self,
+        headers: impl IntoIterator<Item = (K, V)>,
+        body: &[u8],
+    ) -> Result<WebhookEvent, HashGateError>
+    where
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        self.verify_and_parse_at(headers, body, Utc::now().timestamp())
+    }
+
+    /// Verify a webhook delivery and parse its event, as of `now`.
+    fn verify_and_parse_at<K, V>(
+        &self,
+        headers: impl IntoIterator<Item = (K, V)>,
+        body: &[u8],
+        now: i64,
+    ) -> Result<WebhookEvent, HashGateError>
+    where
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        let signature_header = headers
+            .into_iter()
+            .find(|(name, _)| name.as_ref().eq_ignore_ascii_case(SIGNATURE_HEADER))
+            .and_then(|(_, value)| String::from_utf8(value.as_ref().to_vec()).ok())
+            .ok_or_else(|| invalid("Missing Signature Header"))?;
+
+        let timestamp = self.verify_signature(&signature_header, body, now)?;
+
+        let event = serde_json::from_slice::<WebhookEvent>(body)
+            .map_err(|e| HashGateError::MalformedWebhook(e.to_string()))?;
+
+        self.check_replay(event.event_id, timestamp, now)?;
+
+        Ok(event)
+    }
+
+    /// Verify a signature header against the body, returning the signed timestamp.
+    fn verify_signature(
+        &self,
+        signature_header: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<i64, HashGateError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in signature_header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let timestamp = timestamp.ok_or_else(|| invalid("Missing Signature Timestamp"))?;
+        let age = now.abs_diff(timestamp);
+        if age > self.tolerance.as_secs() {
+            return Err(invalid("Signature Timestamp Outside Tolerance"));
+        }
+
+        // Any matching signature is accepted, HashGate sends one per active secret while rotating
+        let is_valid = signatures.iter().any(|signature| {
+            let Some(signature) = decode_hex(signature) else {
+                return false;
+            };
+            let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(&self.secret) else {
+                return false;
+            };
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+            mac.update(body);
+            mac.verify_slice(&signature).is_ok()
+        });
+
+        if is_valid {
+            Ok(timestamp)
+        } else {
+            Err(invalid("Signature Mismatch"))
+        }
+    }
+
+    /// Reject deliveries that were already seen within the tolerance.
+    fn check_replay(&self, event_id: Uuid, timestamp: i64, now: i64) -> Result<(), HashGateError> {
+        // Recovering a poisoned lock keeps the protection, the map is still valid
+        let mut seen_deliveries = self
+            .seen_deliveries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        // Anything older than the tolerance is rejected by the timestamp check anyway
+        let cutoff = now - self.tolerance.as_secs() as i64;
+        seen_deliveries.retain(|(_, seen_at)| *seen_at >= cutoff);
+
+        if !seen_deliveries.insert((event_id, timestamp)) {
+            Err(invalid("Delivery Was Already Seen"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Build an invalid webhook error.
+fn invalid(reason: &str) -> HashGateError {
+    HashGateError::InvalidWebhook(reason.to_owned())
+}
+
+/// Decode a hex string into bytes.
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const BODY: &[u8] = br#"{"hello":"world"}"#;
+    // HMAC-SHA256 of `1700000000.{"hello":"world"}` with the key `whsec_test`
+    const SIGNATURE: &str = "f592bbf3951cfc94e560eecfb5d9dd4da6b0fff2e626235f8ab4b54860925d0b";
+
+    fn verifier() -> WebhookVerifier {
+        WebhookVerifier::new("whsec_test")
+    }
+
+    fn signed_headers(body: &[u8]) -> [(&'static str, String); 1] {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec_test").unwrap();
+        mac.update(format!("{NOW}.").as_bytes());
+        mac.update(body);
+        let signature: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        [(SIGNATURE_HEADER, format!("t={NOW},v1={signature}"))]
+    }
+
+    #[test]
+    fn verifies_known_signature() {
+        let header = format!("t={NOW},v1={SIGNATURE}");
+
+        assert_eq!(
+            verifier().verify_signature(&header, BODY, NOW).unwrap(),
+            NOW
+        );
+        assert!(verifier()
+            .verify_signature(&header, br#"{"hello":"there"}"#, NOW)
+            .is_err());
+        assert!(WebhookVerifier::new("whsec_other")
+            .verify_signature(&header, BODY, NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn accepts_any_matching_signature() {
+        let header = format!("t={NOW}, v1={}, v1={SIGNATURE}", "00".repeat(32));
+
+        assert!(verifier().verify_signature(&header, BODY, NOW).is_ok());
+    }
+
+    #[test]
+    fn checks_tolerance_window() {
+        let header = format!("t={NOW},v1={SIGNATURE}");
+        let verifier = verifier().tolerance(Duration::from_secs(300));
+
+        assert!(verifier.verify_signature(&header, BODY, NOW + 300).is_ok());
+        assert!(verifier.verify_signature(&header, BODY, NOW - 300).is_ok());
+        assert!(verifier.verify_signature(&header, BODY, NOW + 301).is_err());
+        assert!(verifier.verify_signature(&header, BODY, NOW - 301).is_err());
+    }
+
+    #[test]
+    fn rejects_replayed_deliveries_only() {
+        let verifier = verifier();
+        let event_id = Uuid::new_v4();
+
+        assert!(verifier.check_replay(event_id, NOW, NOW).is_ok());
+        assert!(verifier.check_replay(event_id, NOW, NOW).is_err());
+        // A retry is signed with a new timestamp
+        assert!(verifier.check_replay(event_id, NOW + 60, NOW + 60).is_ok());
+    }
+
+    #[test]
+    fn decodes_hex() {
+        assert_eq!(decode_hex("00ff7F"), Some(vec![0x00, 0xff, 0x7f]));
+        assert_eq!(decode_hex(""), Some(Vec::new()));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("é"), None);
+    }
+
+    #[test]
+    fn debug_redacts_secret() {
+        let debug = format!("{:?}", verifier());
+
+        assert!(!debug.contains("whsec_test"));
+        assert!(debug.contains("[REDACTED]"));
+    }
+
+    #[test]
+    fn parses_verified_events() {
+        let body = br#"{
+            "eventId": "6f1c1d8e-52b4-4c39-9a8e-0d6d4f1f2a10",
+            "createdAt": "2023-11-14T22:13:20",
+            "type": "user.deleted",
+            "data": { "userId": "0b6f9a9e-5d3c-4a8f-8f43-3c1e2f7d9b21" }
+        }"#;
+
+        let event = verifier()
+            .verify_and_parse_at(signed_headers(body), body, NOW)
+            .unwrap();
+
+        assert_eq!(event.event_type(), WebhookEventType::UserDeleted);
+        assert!(matches!(
+            event.data,
+            WebhookEventData::UserDeleted { user_id }
+                if user_id.to_string() == "0b6f9a9e-5d3c-4a8f-8f43-3c1e2f7d9b21"
+        ));
+    }
+
+    #[test]
+    fn parses_unknown_event_types() {
+        let body = br#"{
+            "eventId": "6f1c1d8e-52b4-4c39-9a8e-0d6d4f1f2a10",
+            "createdAt": "2023-11-14T22:13:20",
+            "type": "user.renamed",
+            "data": { "userId": "0b6f9a9e-5d3c-4a8f-8f43-3c1e2f7d9b21", "username": "jo" }
+        }"#;
+
+        let event = verifier()
+            .verify_and_parse_at(signed_headers(body), body, NOW)
+            .unwrap();
+
+        assert_eq!(event.event_type(), WebhookEventType::Unknown);
+        assert!(matches!(event.data, WebhookEventData::Unknown));
+    }
+
+    #[test]
+    fn separates_malformed_from_invalid_deliveries() {
+        let malformed = verifier().verify_and_parse_at(signed_headers(BODY), BODY, NOW);
+        assert!(matches!(malformed, Err(HashGateError::MalformedWebhook(_))));
+
+        let unsigned = verifier().verify_and_parse_at([(SIGNATURE_HEADER, "t=0")], BODY, NOW);
+        assert!(matches!(unsigned, Err(HashGateError::InvalidWebhook(_))));
+    }
+}